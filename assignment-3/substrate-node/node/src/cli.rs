@@ -1,4 +1,5 @@
 use sc_cli::RunCmd;
+use std::path::PathBuf;
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -8,6 +9,11 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// File holding the hex-encoded Merkle root of the external system to anchor in every
+	/// block this node authors.
+	#[structopt(long, parse(from_os_str))]
+	pub checkpoint_file: Option<PathBuf>,
 }
 
 #[derive(Debug, StructOpt)]
@@ -110,10 +110,11 @@ pub fn run() -> sc_cli::Result<()> {
 			},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
+			let checkpoint_file = cli.checkpoint_file.clone();
 			runner.run_node_until_exit(|config| async move {
 				match config.role {
 					Role::Light => service::new_light(config),
-					_ => service::new_full(config),
+					_ => service::new_full(config, checkpoint_file),
 				}
 				.map_err(sc_cli::Error::Service)
 			})
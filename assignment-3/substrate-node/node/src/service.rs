@@ -10,7 +10,8 @@ use sc_service::{error::Error as ServiceError, Configuration, TaskManager};
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_consensus::SlotData;
 use sp_consensus_aura::sr25519::AuthorityPair as AuraPair;
-use std::{sync::Arc, time::Duration};
+use sp_core::H256;
+use std::{path::PathBuf, str::FromStr, sync::Arc, time::Duration};
 
 // Our native executor instance.
 pub struct ExecutorDispatch;
@@ -151,8 +152,20 @@ fn remote_keystore(_url: &String) -> Result<Arc<LocalKeystore>, &'static str> {
 	Err("Remote Keystore not supported.")
 }
 
+/// Reads the checkpoint root to anchor in the next authored block.
+///
+/// A missing or malformed file only means there is nothing to anchor: block production must not
+/// stall because the external system has not published a root yet.
+fn read_checkpoint(path: &Option<PathBuf>) -> Option<H256> {
+	let contents = std::fs::read_to_string(path.as_ref()?).ok()?;
+	H256::from_str(contents.trim().trim_start_matches("0x")).ok()
+}
+
 /// Builds a new service for a full client.
-pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError> {
+pub fn new_full(
+	mut config: Configuration,
+	checkpoint_file: Option<PathBuf>,
+) -> Result<TaskManager, ServiceError> {
 	let sc_service::PartialComponents {
 		client,
 		backend,
@@ -258,16 +271,21 @@ pub fn new_full(mut config: Configuration) -> Result<TaskManager, ServiceError>
 				select_chain,
 				block_import,
 				proposer_factory,
-				create_inherent_data_providers: move |_, ()| async move {
-					let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+				create_inherent_data_providers: move |_, ()| {
+					let checkpoint = read_checkpoint(&checkpoint_file);
+					async move {
+						let timestamp = sp_timestamp::InherentDataProvider::from_system_time();
+
+						let slot =
+							sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
+								*timestamp,
+								raw_slot_duration,
+							);
 
-					let slot =
-						sp_consensus_aura::inherents::InherentDataProvider::from_timestamp_and_duration(
-							*timestamp,
-							raw_slot_duration,
-						);
+						let checkpoint = pallet_checkpoint::InherentDataProvider(checkpoint);
 
-					Ok((timestamp, slot))
+						Ok((timestamp, slot, checkpoint))
+					}
 				},
 				force_authoring,
 				backoff_authoring_blocks,
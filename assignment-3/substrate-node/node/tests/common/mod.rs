@@ -0,0 +1,182 @@
+//! Helpers shared by the end-to-end tests, which run the `node-template` binary as a separate
+//! process and talk to it over HTTP JSON-RPC.
+
+// Every test crate includes this module but uses only some of the helpers.
+#![allow(dead_code)]
+
+use codec::{Decode, Encode};
+use frame_system::EventRecord;
+use node_template_runtime::{
+	Balance, Call, Event, Index, SignedExtra, UncheckedExtrinsic, VERSION,
+};
+use serde_json::{json, Value};
+use sp_core::{bytes, hashing, H256};
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{
+	generic::{Era, SignedPayload},
+	AccountId32, MultiAddress,
+};
+use std::{
+	io::{Read, Write},
+	net::TcpStream,
+	process::{Child, Command, Stdio},
+	str::FromStr,
+	thread,
+	time::{Duration, Instant},
+};
+
+/// Ports well away from the defaults, so the tests do not clash with a running node.
+pub const BASE_P2P_PORT: u16 = 31_333;
+pub const BASE_RPC_PORT: u16 = 19_933;
+pub const BASE_WS_PORT: u16 = 19_944;
+
+/// A node process, killed when dropped.
+pub struct Node {
+	process: Child,
+	rpc_port: u16,
+}
+
+impl Node {
+	/// Start node number `index`, on its own ports and database, with the given extra flags.
+	pub fn start(index: u16, flags: &[&str]) -> Node {
+		let rpc_port = BASE_RPC_PORT + index;
+		let process = Command::new(env!("CARGO_BIN_EXE_node-template"))
+			.args(&["--tmp", "--no-prometheus", "--no-telemetry", "--no-mdns"])
+			.args(&["--port", &(BASE_P2P_PORT + index).to_string()])
+			.args(&["--rpc-port", &rpc_port.to_string()])
+			.args(&["--ws-port", &(BASE_WS_PORT + index).to_string()])
+			.args(flags)
+			.stdout(Stdio::null())
+			.stderr(Stdio::null())
+			.spawn()
+			.expect("the node binary is built for integration tests");
+		Node { process, rpc_port }
+	}
+
+	/// Call `method` over HTTP JSON-RPC and return its result, if the node answered with one.
+	pub fn rpc(&self, method: &str, params: Value) -> Option<Value> {
+		let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+		let body = body.to_string();
+		let mut stream = TcpStream::connect(("127.0.0.1", self.rpc_port)).ok()?;
+		write!(
+			stream,
+			"POST / HTTP/1.1\r\nHost: 127.0.0.1\r\nContent-Type: application/json\r\n\
+			 Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+			body.len(),
+			body
+		)
+		.ok()?;
+
+		let mut response = String::new();
+		stream.read_to_string(&mut response).ok()?;
+		let (_, body) = response.split_once("\r\n\r\n")?;
+		let mut response: Value = serde_json::from_str(body).ok()?;
+		match response.get_mut("result")?.take() {
+			Value::Null => None,
+			result => Some(result),
+		}
+	}
+
+	pub fn block_hash(&self, number: u32) -> Option<H256> {
+		let hash = self.rpc("chain_getBlockHash", json!([number]))?;
+		H256::from_str(hash.as_str()?).ok()
+	}
+
+	pub fn finalized(&self) -> Option<(u32, H256)> {
+		let hash = self.rpc("chain_getFinalizedHead", json!([]))?;
+		let header = self.rpc("chain_getHeader", json!([hash]))?;
+		let number = header.get("number")?.as_str()?.trim_start_matches("0x");
+		Some((u32::from_str_radix(number, 16).ok()?, H256::from_str(hash.as_str()?).ok()?))
+	}
+
+	/// Submit `xt` to the transaction pool and return its hash, if the node accepted it.
+	pub fn submit(&self, xt: &UncheckedExtrinsic) -> Option<H256> {
+		let xt = bytes::to_hex(&xt.encode(), false);
+		let hash = self.rpc("author_submitExtrinsic", json!([xt]))?;
+		H256::from_str(hash.as_str()?).ok()
+	}
+
+	/// Decoded value of the storage entry `key` in the state of block `at`.
+	pub fn storage<V: Decode>(&self, key: &[u8], at: H256) -> Option<V> {
+		let value = self.rpc("state_getStorage", json!([bytes::to_hex(key, false), at]))?;
+		let value = bytes::from_hex(value.as_str()?).ok()?;
+		V::decode(&mut &value[..]).ok()
+	}
+
+	/// Nonce and free balance of `who` in the state of block `at`.
+	pub fn account(&self, who: &AccountId32, at: H256) -> Option<(Index, Balance)> {
+		let mut key = storage_key(b"System", b"Account");
+		key.extend(blake2_128_concat(who));
+		// `AccountInfo` starts with the nonce and three reference counters, followed by the
+		// free balance of the account data.
+		let (nonce, _, _, _, free) = self.storage::<(Index, u32, u32, u32, Balance)>(&key, at)?;
+		Some((nonce, free))
+	}
+
+	/// Events deposited by block `at`.
+	pub fn events(&self, at: H256) -> Option<Vec<Event>> {
+		let records: Vec<EventRecord<Event, H256>> =
+			self.storage(&storage_key(b"System", b"Events"), at)?;
+		Some(records.into_iter().map(|record| record.event).collect())
+	}
+}
+
+impl Drop for Node {
+	fn drop(&mut self) {
+		let _ = self.process.kill();
+		let _ = self.process.wait();
+	}
+}
+
+/// Key of the storage value `item` of `pallet`, or the prefix of the map of that name.
+pub fn storage_key(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+	let mut key = hashing::twox_128(pallet).to_vec();
+	key.extend(hashing::twox_128(item));
+	key
+}
+
+/// Map key `key` hashed with `Blake2_128Concat`.
+pub fn blake2_128_concat(key: &impl Encode) -> Vec<u8> {
+	let key = key.encode();
+	let mut hashed = hashing::blake2_128(&key).to_vec();
+	hashed.extend(key);
+	hashed
+}
+
+/// Poll `f` until it returns `Some`, or panic with `what` after `timeout`.
+pub fn wait_for<T>(what: &str, timeout: Duration, mut f: impl FnMut() -> Option<T>) -> T {
+	let deadline = Instant::now() + timeout;
+	loop {
+		if let Some(value) = f() {
+			return value
+		}
+		assert!(Instant::now() < deadline, "timed out waiting for {}", what);
+		thread::sleep(Duration::from_millis(500));
+	}
+}
+
+/// `call` signed by `from` at nonce `nonce`, valid for ever on the chain with `genesis`.
+pub fn sign(from: Sr25519Keyring, call: Call, nonce: Index, genesis: H256) -> UncheckedExtrinsic {
+	let extra: SignedExtra = (
+		frame_system::CheckSpecVersion::new(),
+		frame_system::CheckTxVersion::new(),
+		frame_system::CheckGenesis::new(),
+		frame_system::CheckEra::from(Era::Immortal),
+		frame_system::CheckNonce::from(nonce),
+		frame_system::CheckWeight::new(),
+		pallet_transaction_payment::ChargeTransactionPayment::from(0),
+	);
+	let payload = SignedPayload::from_raw(
+		call,
+		extra,
+		(VERSION.spec_version, VERSION.transaction_version, genesis, genesis, (), (), ()),
+	);
+	let signature = payload.using_encoded(|payload| from.sign(payload));
+	let (call, extra, _) = payload.deconstruct();
+	UncheckedExtrinsic::new_signed(
+		call,
+		MultiAddress::Id(from.to_account_id()),
+		signature.into(),
+		extra,
+	)
+}
@@ -0,0 +1,157 @@
+//! End-to-end test of a storage deal against a development node.
+//!
+//! Starts the `node-template` binary with `--dev`, where Alice authors and finalizes every block
+//! on her own, and drives a deal through proposal, acceptance, attestation and settlement with
+//! signed extrinsics over JSON-RPC. Each step is checked against the events of the blocks that
+//! included it and against the resulting storage, which covers the wiring between the node, the
+//! runtime and the RPC layer that the pallet's unit tests cannot see.
+//!
+//! The test runs the `node-template` binary for a few minutes, so it is ignored by default; run
+//! it with `cargo test -p node-template --test dev_node -- --ignored`.
+
+mod common;
+
+use common::{blake2_128_concat, sign, storage_key, wait_for, Node};
+use node_template_runtime::{
+	pallet_storage_deal::{self, Deal, DealId, DealStatus, ProviderInfo},
+	Balance, BlockNumber, Call, Event, ProviderBond, Runtime, HOURS, MILLIUNIT,
+};
+use serde_json::json;
+use sp_core::H256;
+use sp_keyring::Sr25519Keyring::{self, Alice, Bob};
+use sp_runtime::AccountId32;
+use std::time::Duration;
+
+type DealCall = pallet_storage_deal::Call<Runtime>;
+type DealEvent = pallet_storage_deal::Event<Runtime>;
+
+/// How long a single extrinsic may take to be finalized.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A development node, and the last of its blocks whose events the test has looked at.
+struct DevNode {
+	node: Node,
+	genesis: H256,
+	seen: u32,
+}
+
+impl DevNode {
+	fn start() -> DevNode {
+		let node = Node::start(0, &["--dev"]);
+		wait_for("the first finalized block", TIMEOUT, || {
+			node.finalized().filter(|(number, _)| *number >= 1)
+		});
+		let genesis = node.block_hash(0).expect("the node knows its genesis");
+		DevNode { node, genesis, seen: 0 }
+	}
+
+	/// Submit `call` signed by `who` and wait until it is finalized. Returns the storage deal
+	/// events of the blocks finalized in the meantime, and the hash of the last of them.
+	fn execute(&mut self, who: Sr25519Keyring, call: DealCall) -> (Vec<DealEvent>, H256) {
+		let account = who.to_account_id();
+		let (_, head) = self.node.finalized().expect("the node is running");
+		let (nonce, _) = self.node.account(&account, head).expect("the signer is endowed");
+		let xt = sign(who, Call::StorageDeal(call), nonce, self.genesis);
+		self.node.submit(&xt).expect("the node accepts the extrinsic");
+
+		let (number, head) = wait_for("the extrinsic to be finalized", TIMEOUT, || {
+			let (number, head) = self.node.finalized()?;
+			let (next, _) = self.node.account(&account, head)?;
+			Some((number, head)).filter(|_| next > nonce)
+		});
+		let events = (self.seen + 1..=number)
+			.flat_map(|number| {
+				let hash = self.node.block_hash(number).expect("finalized blocks are known");
+				self.node.events(hash).expect("every block has events")
+			})
+			.filter_map(|event| match event {
+				Event::StorageDeal(event) => Some(event),
+				_ => None,
+			})
+			.collect();
+		self.seen = number;
+		(events, head)
+	}
+
+	fn provider(&self, who: &AccountId32, at: H256) -> Option<ProviderInfo<Balance>> {
+		let mut key = storage_key(b"StorageDeal", b"Providers");
+		key.extend(blake2_128_concat(who));
+		self.node.storage(&key, at)
+	}
+
+	fn deal(&self, deal_id: DealId, at: H256) -> Option<Deal<Runtime>> {
+		let mut key = storage_key(b"StorageDeal", b"Deals");
+		key.extend(blake2_128_concat(&deal_id));
+		self.node.storage(&key, at)
+	}
+
+	fn last_attestation(
+		&self,
+		deal_id: DealId,
+		who: &AccountId32,
+		at: H256,
+	) -> Option<BlockNumber> {
+		let mut key = storage_key(b"StorageDeal", b"Attestations");
+		key.extend(blake2_128_concat(&deal_id));
+		key.extend(blake2_128_concat(who));
+		self.node.storage(&key, at)
+	}
+}
+
+#[test]
+#[ignore]
+fn storage_deal_lifecycle_over_rpc() {
+	let mut dev = DevNode::start();
+	assert_eq!(dev.node.rpc("system_chain", json!([])), Some(json!("Development")));
+
+	let alice = Alice.to_account_id();
+	let bob = Bob.to_account_id();
+	let content = H256::repeat_byte(7);
+	let price_per_block = MILLIUNIT;
+	let duration = HOURS;
+
+	// Bob becomes a provider.
+	let (events, head) = dev.execute(Bob, DealCall::register_provider {});
+	let bond = ProviderBond::get();
+	assert_eq!(events, vec![DealEvent::ProviderRegistered(bob.clone(), bond)]);
+	assert_eq!(dev.provider(&bob, head), Some(ProviderInfo { bond, active_deals: 0 }));
+
+	// Alice asks for a single replica and escrows the whole price.
+	let (events, head) = dev.execute(
+		Alice,
+		DealCall::propose_deal { content, size: 1_024, replication: 1, price_per_block, duration },
+	);
+	let escrow = price_per_block * duration as Balance;
+	assert_eq!(events, vec![DealEvent::DealProposed(0, alice.clone(), content, escrow)]);
+	let deal = dev.deal(0, head).expect("the deal is stored");
+	assert_eq!(deal.status, DealStatus::Proposed);
+	assert_eq!(deal.escrow, escrow);
+
+	// Taking the only replica activates the deal.
+	let (events, head) = dev.execute(Bob, DealCall::accept_deal { deal_id: 0 });
+	let deal = dev.deal(0, head).expect("the deal is stored");
+	let start = deal.start.expect("the deal is active");
+	assert_eq!(deal.status, DealStatus::Active);
+	assert_eq!(deal.providers.to_vec(), vec![bob.clone()]);
+	assert_eq!(
+		events,
+		vec![DealEvent::DealAccepted(0, bob.clone()), DealEvent::DealActivated(0, start)]
+	);
+	assert_eq!(dev.provider(&bob, head), Some(ProviderInfo { bond, active_deals: 1 }));
+
+	let (events, head) = dev.execute(Bob, DealCall::attest { deal_id: 0 });
+	let attested = dev.last_attestation(0, &bob, head).expect("the attestation is stored");
+	assert!(attested > start);
+	assert_eq!(events, vec![DealEvent::StorageAttested(0, bob.clone(), attested)]);
+
+	// Anyone may settle; Bob is paid for every block since the deal started.
+	let (_, bob_before) = dev.node.account(&bob, head).expect("Bob is endowed");
+	let (events, head) = dev.execute(Alice, DealCall::settle { deal_id: 0 });
+	let deal = dev.deal(0, head).expect("the deal runs for an hour");
+	let paid = price_per_block * (deal.settled_until - start) as Balance;
+	assert!(paid > 0);
+	assert_eq!(events, vec![DealEvent::DealSettled(0, paid)]);
+	assert_eq!(deal.escrow, escrow - paid);
+	let (_, bob_after) = dev.node.account(&bob, head).expect("Bob is endowed");
+	assert_eq!(bob_after, bob_before + paid);
+}
@@ -0,0 +1,76 @@
+//! End-to-end test of a small local network.
+//!
+//! Starts the two `local` chain authorities, Alice and Bob, plus a full node as separate
+//! processes. A balance transfer is submitted to Bob's node, and the test waits until it shows
+//! up in the finalized state of the full node. This covers block authoring, transaction and
+//! block propagation, and GRANDPA finality in one go.
+//!
+//! The test runs the `node-template` binary for up to a few minutes, so it is ignored by
+//! default; run it with `cargo test -p node-template --test network -- --ignored`.
+
+mod common;
+
+use common::{sign, wait_for, Node, BASE_P2P_PORT};
+use node_template_runtime::{Balance, BalancesCall, Call, Index, UncheckedExtrinsic, UNIT};
+use sp_core::H256;
+use sp_keyring::Sr25519Keyring;
+use sp_runtime::{AccountId32, MultiAddress};
+use std::time::Duration;
+
+/// Node key of Alice, so that the other nodes can use her as their bootnode.
+const ALICE_NODE_KEY: &str = "0000000000000000000000000000000000000000000000000000000000000001";
+/// Peer id derived from `ALICE_NODE_KEY`.
+const ALICE_PEER_ID: &str = "12D3KooWEyoppNCUx8Yx66oV9fJnriXwCcXwDDUA2kj6vnc6iDEp";
+
+/// Start node number `index` of the `local` network, with Alice as its bootnode.
+fn start(index: u16, flags: &[&str]) -> Node {
+	let bootnode = format!("/ip4/127.0.0.1/tcp/{}/p2p/{}", BASE_P2P_PORT, ALICE_PEER_ID);
+	let mut args = vec!["--chain", "local", "--bootnodes", bootnode.as_str()];
+	args.extend(flags);
+	Node::start(index, &args)
+}
+
+/// A transfer of `value` from `from` to `to`, signed at nonce `nonce`.
+fn transfer(
+	from: Sr25519Keyring,
+	to: &AccountId32,
+	value: Balance,
+	nonce: Index,
+	genesis: H256,
+) -> UncheckedExtrinsic {
+	let call = Call::Balances(BalancesCall::transfer { dest: MultiAddress::Id(to.clone()), value });
+	sign(from, call, nonce, genesis)
+}
+
+#[test]
+#[ignore]
+fn transfer_is_finalized_across_the_network() {
+	let _alice = start(0, &["--alice", "--node-key", ALICE_NODE_KEY]);
+	let bob = start(1, &["--bob"]);
+	let full = start(2, &[]);
+
+	// The full node only finalizes blocks once both authorities' votes reach it.
+	let timeout = Duration::from_secs(180);
+	wait_for("the full node to finalize blocks", timeout, || {
+		full.finalized().filter(|(number, _)| *number >= 2)
+	});
+
+	let genesis = wait_for("the genesis hash", timeout, || bob.block_hash(0));
+	assert_eq!(full.block_hash(0), Some(genesis));
+	let alice = Sr25519Keyring::Alice.to_account_id();
+	let dave = Sr25519Keyring::Dave.to_account_id();
+	let (_, dave_before) = full.account(&dave, genesis).expect("Dave is endowed at genesis");
+
+	let xt = transfer(Sr25519Keyring::Alice, &dave, UNIT, 0, genesis);
+	bob.submit(&xt).expect("Bob's node accepts the transfer");
+
+	let (alice_nonce, dave_after) =
+		wait_for("the transfer to be finalized on the full node", timeout, || {
+			let (_, finalized) = full.finalized()?;
+			let (nonce, _) = full.account(&alice, finalized)?;
+			let (_, free) = full.account(&dave, finalized)?;
+			Some((nonce, free)).filter(|(nonce, _)| *nonce > 0)
+		});
+	assert_eq!(alice_nonce, 1);
+	assert_eq!(dave_after, dave_before + UNIT);
+}
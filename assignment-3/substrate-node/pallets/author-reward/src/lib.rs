@@ -0,0 +1,128 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Block author rewards.
+///
+/// Every block mints `RewardPerBlock` to the author of the block, as reported by the configured
+/// `FindAuthor`. The pallet also implements `OnUnbalanced`, so it can be plugged into
+/// transaction payment to credit collected fees and tips to the same author. When no author can
+/// be found, both go to the `FallbackAccount` instead (typically the treasury).
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		dispatch::DispatchResult,
+		pallet_prelude::*,
+		traits::{Currency, FindAuthor, Imbalance, OnUnbalanced},
+	};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::Zero;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	pub type NegativeImbalanceOf<T> = <<T as Config>::Currency as Currency<
+		<T as frame_system::Config>::AccountId,
+	>>::NegativeImbalance;
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Currency the rewards are minted in.
+		type Currency: Currency<Self::AccountId>;
+		/// Finds the account that authored the current block from its pre-runtime digests.
+		type FindAuthor: FindAuthor<Self::AccountId>;
+		/// Receives the reward and fees of blocks whose author cannot be found.
+		type FallbackAccount: Get<Self::AccountId>;
+		/// Reward per block until governance sets one.
+		#[pallet::constant]
+		type DefaultReward: Get<BalanceOf<Self>>;
+		/// Origin allowed to change the reward.
+		type UpdateOrigin: EnsureOrigin<Self::Origin>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::type_value]
+	pub fn DefaultRewardPerBlock<T: Config>() -> BalanceOf<T> {
+		T::DefaultReward::get()
+	}
+
+	#[pallet::storage]
+	#[pallet::getter(fn reward_per_block)]
+	pub type RewardPerBlock<T: Config> =
+		StorageValue<_, BalanceOf<T>, ValueQuery, DefaultRewardPerBlock<T>>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// [beneficiary, reward]
+		AuthorRewarded(T::AccountId, BalanceOf<T>),
+		/// [beneficiary, fees]
+		FeesCredited(T::AccountId, BalanceOf<T>),
+		/// [reward]
+		RewardUpdated(BalanceOf<T>),
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			let reward = RewardPerBlock::<T>::get();
+			if !reward.is_zero() {
+				let beneficiary = Self::beneficiary();
+				// Minting fails when it would create an account below the existential deposit.
+				let minted = T::Currency::deposit_creating(&beneficiary, reward).peek();
+				if !minted.is_zero() {
+					Self::deposit_event(Event::AuthorRewarded(beneficiary, minted));
+				}
+			}
+			T::DbWeight::get().reads_writes(3, 2)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Change the amount minted to the block author on every block.
+		#[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+		pub fn set_reward(origin: OriginFor<T>, reward: BalanceOf<T>) -> DispatchResult {
+			T::UpdateOrigin::ensure_origin(origin)?;
+			RewardPerBlock::<T>::put(reward);
+			Self::deposit_event(Event::RewardUpdated(reward));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Author of the current block, or the fallback account if it cannot be determined.
+		pub fn beneficiary() -> T::AccountId {
+			let digest = <frame_system::Pallet<T>>::digest();
+			let pre_runtime_digests = digest.logs.iter().filter_map(|d| d.as_pre_runtime());
+			T::FindAuthor::find_author(pre_runtime_digests)
+				.unwrap_or_else(|| T::FallbackAccount::get())
+		}
+	}
+
+	/// Credits transaction fees and tips to the block author.
+	impl<T: Config> OnUnbalanced<NegativeImbalanceOf<T>> for Pallet<T> {
+		fn on_nonzero_unbalanced(amount: NegativeImbalanceOf<T>) {
+			let beneficiary = Self::beneficiary();
+			let deposit = T::Currency::deposit_creating(&beneficiary, amount.peek());
+			let credited = deposit.peek();
+			// Whatever could not be credited, e.g. below the existential deposit, is burned.
+			let _ = amount.offset(deposit);
+			if !credited.is_zero() {
+				Self::deposit_event(Event::FeesCredited(beneficiary, credited));
+			}
+		}
+	}
+}
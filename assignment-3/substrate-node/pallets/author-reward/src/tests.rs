@@ -0,0 +1,83 @@
+use crate::{mock::*, Event};
+use codec::Encode;
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{Currency, OnInitialize, OnUnbalanced},
+};
+use sp_runtime::{DigestItem, DispatchError};
+
+fn authored_by(author: u64) {
+	System::deposit_log(DigestItem::PreRuntime(TEST_ENGINE_ID, author.encode()));
+}
+
+#[test]
+fn author_receives_block_reward() {
+	new_test_ext().execute_with(|| {
+		authored_by(7);
+		AuthorReward::on_initialize(1);
+		assert_eq!(Balances::free_balance(7), 10);
+		assert_eq!(Balances::total_issuance(), 10);
+		System::assert_last_event(Event::AuthorRewarded(7, 10).into());
+	});
+}
+
+#[test]
+fn failed_mint_is_not_reported() {
+	new_test_ext().execute_with(|| {
+		// Below the existential deposit, the reward cannot create the author's account.
+		assert_ok!(AuthorReward::set_reward(Origin::root(), 3));
+		System::reset_events();
+
+		authored_by(7);
+		AuthorReward::on_initialize(1);
+		assert_eq!(Balances::free_balance(7), 0);
+		assert_eq!(Balances::total_issuance(), 0);
+		assert!(System::events().is_empty());
+	});
+}
+
+#[test]
+fn reward_falls_back_without_author() {
+	new_test_ext().execute_with(|| {
+		AuthorReward::on_initialize(1);
+		assert_eq!(Balances::free_balance(TREASURY), 10);
+	});
+}
+
+#[test]
+fn fees_are_credited_to_author() {
+	new_test_ext().execute_with(|| {
+		authored_by(7);
+		let fee = <Balances as Currency<u64>>::issue(25);
+		AuthorReward::on_unbalanced(fee);
+		assert_eq!(Balances::free_balance(7), 25);
+		assert_eq!(Balances::total_issuance(), 25);
+		System::assert_last_event(Event::FeesCredited(7, 25).into());
+	});
+}
+
+#[test]
+fn uncredited_fees_are_not_reported() {
+	new_test_ext().execute_with(|| {
+		// Below the existential deposit, the fee cannot create the author's account and is burned.
+		authored_by(7);
+		let fee = <Balances as Currency<u64>>::issue(3);
+		AuthorReward::on_unbalanced(fee);
+		assert_eq!(Balances::free_balance(7), 0);
+		assert_eq!(Balances::total_issuance(), 0);
+		assert!(System::events().is_empty());
+	});
+}
+
+#[test]
+fn set_reward_requires_update_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(AuthorReward::set_reward(Origin::signed(1), 5), DispatchError::BadOrigin);
+		assert_ok!(AuthorReward::set_reward(Origin::root(), 0));
+		assert_eq!(AuthorReward::reward_per_block(), 0);
+
+		authored_by(7);
+		AuthorReward::on_initialize(2);
+		assert_eq!(Balances::free_balance(7), 0);
+	});
+}
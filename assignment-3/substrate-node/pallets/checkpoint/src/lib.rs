@@ -0,0 +1,172 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Checkpoints of an external system anchored by block authors.
+///
+/// The authoring node reads the latest Merkle root of the external system and hands it to the
+/// runtime through [`InherentDataProvider`]; the runtime turns it into a `note_checkpoint`
+/// inherent recorded under the block number. The call is unsigned and the pallet does not
+/// implement `ValidateUnsigned`, so the transaction pool refuses it: the block author's inherent
+/// is the only way in, and at most once per block.
+pub use pallet::*;
+
+#[cfg(feature = "std")]
+use codec::Decode;
+use codec::Encode;
+use sp_inherents::{InherentIdentifier, IsFatalError};
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+/// The identifier of the checkpoint inherent.
+pub const INHERENT_IDENTIFIER: InherentIdentifier = *b"chkpoint";
+
+/// Errors reported when checking a checkpoint inherent.
+#[derive(Encode, sp_runtime::RuntimeDebug)]
+#[cfg_attr(feature = "std", derive(Decode))]
+pub enum InherentError {
+	/// The inherent carries the default (all-zero) root.
+	EmptyRoot,
+}
+
+impl IsFatalError for InherentError {
+	fn is_fatal_error(&self) -> bool {
+		true
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::{InherentError, INHERENT_IDENTIFIER};
+	use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
+	use frame_system::pallet_prelude::*;
+	use sp_inherents::{InherentData, InherentIdentifier};
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Root of the external system anchored in each block that carried one.
+	#[pallet::storage]
+	#[pallet::getter(fn checkpoint)]
+	pub type Checkpoints<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, T::Hash, OptionQuery>;
+
+	/// Whether a checkpoint was already noted in the current block.
+	#[pallet::storage]
+	pub type DidUpdate<T> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// [block, root]
+		CheckpointNoted(T::BlockNumber, T::Hash),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A checkpoint was already noted in this block.
+		AlreadyNoted,
+		/// The root is the default (all-zero) hash.
+		EmptyRoot,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(_n: T::BlockNumber) -> Weight {
+			// Accounts for clearing `DidUpdate` in `on_finalize`.
+			T::DbWeight::get().writes(1)
+		}
+
+		fn on_finalize(_n: T::BlockNumber) {
+			DidUpdate::<T>::kill();
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Anchor the external system's root in the current block.
+		///
+		/// Only ever included as an inherent by the block author.
+		#[pallet::weight((
+			10_000 + T::DbWeight::get().reads_writes(1, 2),
+			DispatchClass::Mandatory
+		))]
+		pub fn note_checkpoint(origin: OriginFor<T>, root: T::Hash) -> DispatchResult {
+			ensure_none(origin)?;
+			ensure!(!DidUpdate::<T>::exists(), Error::<T>::AlreadyNoted);
+			ensure!(root != T::Hash::default(), Error::<T>::EmptyRoot);
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			Checkpoints::<T>::insert(now, root);
+			DidUpdate::<T>::put(true);
+
+			Self::deposit_event(Event::CheckpointNoted(now, root));
+			Ok(())
+		}
+	}
+
+	#[pallet::inherent]
+	impl<T: Config> ProvideInherent for Pallet<T> {
+		type Call = Call<T>;
+		type Error = InherentError;
+		const INHERENT_IDENTIFIER: InherentIdentifier = INHERENT_IDENTIFIER;
+
+		fn create_inherent(data: &InherentData) -> Option<Self::Call> {
+			let root = data.get_data::<T::Hash>(&INHERENT_IDENTIFIER).ok().flatten()?;
+			Some(Call::note_checkpoint { root })
+		}
+
+		fn check_inherent(call: &Self::Call, _data: &InherentData) -> Result<(), Self::Error> {
+			match call {
+				Call::note_checkpoint { root } if *root == T::Hash::default() =>
+					Err(InherentError::EmptyRoot),
+				_ => Ok(()),
+			}
+		}
+
+		fn is_inherent(call: &Self::Call) -> bool {
+			matches!(call, Call::note_checkpoint { .. })
+		}
+	}
+}
+
+/// Provides the root to anchor in blocks authored by this node, if there is one.
+#[cfg(feature = "std")]
+pub struct InherentDataProvider<H>(pub Option<H>);
+
+#[cfg(feature = "std")]
+#[async_trait::async_trait]
+impl<H: Encode + Send + Sync> sp_inherents::InherentDataProvider for InherentDataProvider<H> {
+	fn provide_inherent_data(
+		&self,
+		inherent_data: &mut sp_inherents::InherentData,
+	) -> Result<(), sp_inherents::Error> {
+		match &self.0 {
+			Some(root) => inherent_data.put_data(INHERENT_IDENTIFIER, root),
+			None => Ok(()),
+		}
+	}
+
+	async fn try_handle_error(
+		&self,
+		identifier: &InherentIdentifier,
+		error: &[u8],
+	) -> Option<Result<(), sp_inherents::Error>> {
+		if *identifier != INHERENT_IDENTIFIER {
+			return None
+		}
+
+		let error = InherentError::decode(&mut &error[..]).ok()?;
+		Some(Err(sp_inherents::Error::Application(Box::from(format!("{:?}", error)))))
+	}
+}
@@ -0,0 +1,81 @@
+use crate::{
+	mock::*, pallet::Call as CheckpointCall, Error, Event, InherentError, INHERENT_IDENTIFIER,
+};
+use frame_support::{assert_noop, assert_ok, inherent::ProvideInherent, traits::OnFinalize};
+use sp_core::H256;
+use sp_inherents::InherentData;
+use sp_runtime::DispatchError;
+
+fn root(byte: u8) -> H256 {
+	H256::repeat_byte(byte)
+}
+
+#[test]
+fn note_checkpoint_records_root_under_block_number() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Checkpoint::note_checkpoint(Origin::none(), root(1)));
+		assert_eq!(Checkpoint::checkpoint(1), Some(root(1)));
+		System::assert_last_event(Event::CheckpointNoted(1, root(1)).into());
+	});
+}
+
+#[test]
+fn note_checkpoint_must_be_unsigned() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Checkpoint::note_checkpoint(Origin::signed(1), root(1)),
+			DispatchError::BadOrigin
+		);
+		assert_noop!(
+			Checkpoint::note_checkpoint(Origin::root(), root(1)),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn only_one_checkpoint_per_block() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Checkpoint::note_checkpoint(Origin::none(), root(1)));
+		assert_noop!(
+			Checkpoint::note_checkpoint(Origin::none(), root(2)),
+			Error::<Test>::AlreadyNoted
+		);
+
+		Checkpoint::on_finalize(1);
+		System::set_block_number(2);
+		assert_ok!(Checkpoint::note_checkpoint(Origin::none(), root(2)));
+		assert_eq!(Checkpoint::checkpoint(1), Some(root(1)));
+		assert_eq!(Checkpoint::checkpoint(2), Some(root(2)));
+	});
+}
+
+#[test]
+fn empty_root_is_rejected() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Checkpoint::note_checkpoint(Origin::none(), H256::zero()),
+			Error::<Test>::EmptyRoot
+		);
+
+		let call = CheckpointCall::note_checkpoint { root: H256::zero() };
+		assert!(matches!(
+			Checkpoint::check_inherent(&call, &InherentData::new()),
+			Err(InherentError::EmptyRoot)
+		));
+	});
+}
+
+#[test]
+fn inherent_is_created_from_provided_root() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Checkpoint::create_inherent(&InherentData::new()), None);
+
+		let mut data = InherentData::new();
+		data.put_data(INHERENT_IDENTIFIER, &root(7)).unwrap();
+		let call = Checkpoint::create_inherent(&data).unwrap();
+		assert_eq!(call, CheckpointCall::note_checkpoint { root: root(7) });
+		assert!(Checkpoint::is_inherent(&call));
+		assert!(Checkpoint::check_inherent(&call, &data).is_ok());
+	});
+}
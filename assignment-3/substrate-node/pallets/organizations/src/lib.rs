@@ -0,0 +1,234 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Organization accounts with internal roles.
+///
+/// Every organization owns a sovereign account derived from `PalletId` and its id. Members never
+/// share a key for it: they submit `org_execute`, which dispatches the call from the sovereign
+/// account once the runtime's `RoleFilter` agrees that the caller's role may make it.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		dispatch::{DispatchResult, Dispatchable, GetDispatchInfo, PostDispatchInfo},
+		pallet_prelude::*,
+		PalletId,
+	};
+	use frame_system::pallet_prelude::*;
+	use scale_info::TypeInfo;
+	use sp_runtime::traits::AccountIdConversion;
+	use sp_std::prelude::*;
+
+	pub type OrgId = u32;
+
+	#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum Role {
+		/// Manages the members and may make any allowed call.
+		Admin,
+		/// Runs the organization's day-to-day registry business.
+		Registrar,
+		/// Inspects the organization's activity.
+		Auditor,
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct OrgInfo {
+		/// Number of members, admins included.
+		pub members: u32,
+		/// Number of members with the `Admin` role.
+		pub admins: u32,
+	}
+
+	/// Decides which calls a member with a given role may make for its organization.
+	pub trait RoleFilter<Call> {
+		fn allows(role: Role, call: &Call) -> bool;
+	}
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// The calls members can make on behalf of an organization.
+		type Call: Parameter
+			+ Dispatchable<Origin = Self::Origin, PostInfo = PostDispatchInfo>
+			+ GetDispatchInfo;
+		/// Derives the sovereign account of each organization.
+		#[pallet::constant]
+		type PalletId: Get<PalletId>;
+		/// Maximum number of members of one organization.
+		#[pallet::constant]
+		type MaxMembers: Get<u32>;
+		/// Which calls each role may make.
+		type RoleFilter: RoleFilter<<Self as Config>::Call>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	#[pallet::getter(fn organizations)]
+	pub type Organizations<T> = StorageMap<_, Blake2_128Concat, OrgId, OrgInfo>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn role_of)]
+	pub type Members<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, OrgId, Blake2_128Concat, T::AccountId, Role>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_org_id)]
+	pub type NextOrgId<T> = StorageValue<_, OrgId, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// [org_id, creator, account]
+		OrgCreated(OrgId, T::AccountId, T::AccountId),
+		/// [org_id, member, role]
+		MemberAdded(OrgId, T::AccountId, Role),
+		/// [org_id, member]
+		MemberRemoved(OrgId, T::AccountId),
+		/// [org_id, member, result]
+		OrgExecuted(OrgId, T::AccountId, DispatchResult),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// No organization with this id.
+		OrgNotFound,
+		/// The organization counter overflowed.
+		Overflow,
+		/// The account is not a member of the organization.
+		NotMember,
+		/// Only admins of the organization may do this.
+		NotAdmin,
+		/// The account is already a member of the organization.
+		AlreadyMember,
+		/// The organization already has `MaxMembers` members.
+		TooManyMembers,
+		/// An organization must keep at least one admin.
+		LastAdmin,
+		/// The caller's role may not make this call.
+		CallFiltered,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Create an organization with the caller as its first admin.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 3))]
+		pub fn create_org(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let org_id = NextOrgId::<T>::get();
+			let next_id = org_id.checked_add(1).ok_or(Error::<T>::Overflow)?;
+
+			Organizations::<T>::insert(org_id, OrgInfo { members: 1, admins: 1 });
+			Members::<T>::insert(org_id, &who, Role::Admin);
+			NextOrgId::<T>::put(next_id);
+
+			Self::deposit_event(Event::OrgCreated(org_id, who, Self::account_id(org_id)));
+			Ok(())
+		}
+
+		/// Add a member with the given role. Only admins may do this.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+		pub fn add_member(
+			origin: OriginFor<T>,
+			org_id: OrgId,
+			member: T::AccountId,
+			role: Role,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_admin(org_id, &who)?;
+			ensure!(!Members::<T>::contains_key(org_id, &member), Error::<T>::AlreadyMember);
+
+			Organizations::<T>::try_mutate(org_id, |maybe_info| -> DispatchResult {
+				let info = maybe_info.as_mut().ok_or(Error::<T>::OrgNotFound)?;
+				ensure!(info.members < T::MaxMembers::get(), Error::<T>::TooManyMembers);
+				info.members += 1;
+				if role == Role::Admin {
+					info.admins += 1;
+				}
+				Ok(())
+			})?;
+			Members::<T>::insert(org_id, &member, role);
+
+			Self::deposit_event(Event::MemberAdded(org_id, member, role));
+			Ok(())
+		}
+
+		/// Remove a member. Only admins may do this, and the last admin cannot be removed.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(3, 2))]
+		pub fn remove_member(
+			origin: OriginFor<T>,
+			org_id: OrgId,
+			member: T::AccountId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::ensure_admin(org_id, &who)?;
+			let role = Members::<T>::get(org_id, &member).ok_or(Error::<T>::NotMember)?;
+
+			Organizations::<T>::try_mutate(org_id, |maybe_info| -> DispatchResult {
+				let info = maybe_info.as_mut().ok_or(Error::<T>::OrgNotFound)?;
+				if role == Role::Admin {
+					ensure!(info.admins > 1, Error::<T>::LastAdmin);
+					info.admins -= 1;
+				}
+				info.members -= 1;
+				Ok(())
+			})?;
+			Members::<T>::remove(org_id, &member);
+
+			Self::deposit_event(Event::MemberRemoved(org_id, member));
+			Ok(())
+		}
+
+		/// Dispatch `call` from the organization's sovereign account, if the caller's role
+		/// allows it.
+		///
+		/// The outcome of the call is reported in `OrgExecuted`; this extrinsic itself only
+		/// fails when the caller may not make the call.
+		#[pallet::weight({
+			let info = call.get_dispatch_info();
+			(info.weight.saturating_add(10_000 + T::DbWeight::get().reads(2)), info.class)
+		})]
+		pub fn org_execute(
+			origin: OriginFor<T>,
+			org_id: OrgId,
+			call: Box<<T as Config>::Call>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(Organizations::<T>::contains_key(org_id), Error::<T>::OrgNotFound);
+			let role = Members::<T>::get(org_id, &who).ok_or(Error::<T>::NotMember)?;
+			ensure!(T::RoleFilter::allows(role, &call), Error::<T>::CallFiltered);
+
+			let origin = frame_system::RawOrigin::Signed(Self::account_id(org_id)).into();
+			let result = call.dispatch(origin).map(|_| ()).map_err(|e| e.error);
+
+			Self::deposit_event(Event::OrgExecuted(org_id, who, result));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		/// Sovereign account of the organization.
+		pub fn account_id(org_id: OrgId) -> T::AccountId {
+			T::PalletId::get().into_sub_account(org_id)
+		}
+
+		fn ensure_admin(org_id: OrgId, who: &T::AccountId) -> DispatchResult {
+			ensure!(Organizations::<T>::contains_key(org_id), Error::<T>::OrgNotFound);
+			match Members::<T>::get(org_id, who) {
+				Some(Role::Admin) => Ok(()),
+				Some(_) => Err(Error::<T>::NotAdmin.into()),
+				None => Err(Error::<T>::NotMember.into()),
+			}
+		}
+	}
+}
@@ -0,0 +1,129 @@
+use crate::{mock::*, Error, Event as OrgEvent, OrgInfo, Role};
+use frame_support::{assert_noop, assert_ok};
+
+/// Creates organization 0 with a registrar and an auditor besides its admin.
+fn org() -> u32 {
+	assert_ok!(Organizations::create_org(Origin::signed(ADMIN)));
+	assert_ok!(Organizations::add_member(Origin::signed(ADMIN), 0, REGISTRAR, Role::Registrar));
+	assert_ok!(Organizations::add_member(Origin::signed(ADMIN), 0, AUDITOR, Role::Auditor));
+	0
+}
+
+fn transfer(dest: u128, value: u64) -> Box<Call> {
+	Box::new(Call::Balances(pallet_balances::Call::transfer { dest, value }))
+}
+
+#[test]
+fn create_org_makes_caller_admin() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Organizations::create_org(Origin::signed(ADMIN)));
+		assert_ok!(Organizations::create_org(Origin::signed(REGISTRAR)));
+
+		assert_eq!(Organizations::role_of(0, ADMIN), Some(Role::Admin));
+		assert_eq!(Organizations::organizations(0), Some(OrgInfo { members: 1, admins: 1 }));
+		assert_eq!(Organizations::next_org_id(), 2);
+		// Each organization has its own sovereign account.
+		assert_ne!(Organizations::account_id(0), Organizations::account_id(1));
+		System::assert_last_event(
+			OrgEvent::OrgCreated(1, REGISTRAR, Organizations::account_id(1)).into(),
+		);
+	});
+}
+
+#[test]
+fn only_admins_manage_members() {
+	new_test_ext().execute_with(|| {
+		let org_id = org();
+		assert_eq!(Organizations::organizations(org_id), Some(OrgInfo { members: 3, admins: 1 }));
+
+		assert_noop!(
+			Organizations::add_member(Origin::signed(REGISTRAR), org_id, 4, Role::Admin),
+			Error::<Test>::NotAdmin
+		);
+		assert_noop!(
+			Organizations::remove_member(Origin::signed(4), org_id, AUDITOR),
+			Error::<Test>::NotMember
+		);
+		assert_noop!(
+			Organizations::add_member(Origin::signed(ADMIN), org_id, AUDITOR, Role::Admin),
+			Error::<Test>::AlreadyMember
+		);
+		assert_noop!(
+			Organizations::add_member(Origin::signed(ADMIN), org_id, 4, Role::Auditor),
+			Error::<Test>::TooManyMembers
+		);
+
+		assert_ok!(Organizations::remove_member(Origin::signed(ADMIN), org_id, AUDITOR));
+		assert_eq!(Organizations::role_of(org_id, AUDITOR), None);
+		assert_eq!(Organizations::organizations(org_id), Some(OrgInfo { members: 2, admins: 1 }));
+	});
+}
+
+#[test]
+fn last_admin_cannot_be_removed() {
+	new_test_ext().execute_with(|| {
+		let org_id = org();
+		assert_noop!(
+			Organizations::remove_member(Origin::signed(ADMIN), org_id, ADMIN),
+			Error::<Test>::LastAdmin
+		);
+
+		assert_ok!(Organizations::remove_member(Origin::signed(ADMIN), org_id, AUDITOR));
+		assert_ok!(Organizations::add_member(Origin::signed(ADMIN), org_id, 4, Role::Admin));
+		assert_ok!(Organizations::remove_member(Origin::signed(4), org_id, ADMIN));
+		assert_eq!(Organizations::organizations(org_id), Some(OrgInfo { members: 2, admins: 1 }));
+	});
+}
+
+#[test]
+fn org_execute_dispatches_from_sovereign_account() {
+	new_test_ext().execute_with(|| {
+		let org_id = org();
+		let account = Organizations::account_id(org_id);
+		assert_ok!(Balances::transfer(Origin::signed(ADMIN), account, 500));
+
+		assert_ok!(Organizations::org_execute(Origin::signed(REGISTRAR), org_id, transfer(4, 200)));
+		assert_eq!(Balances::free_balance(account), 300);
+		assert_eq!(Balances::free_balance(4), 200);
+		// The registrar's own balance is untouched.
+		assert_eq!(Balances::free_balance(REGISTRAR), 1_000);
+		System::assert_last_event(OrgEvent::OrgExecuted(org_id, REGISTRAR, Ok(())).into());
+	});
+}
+
+#[test]
+fn org_execute_reports_failed_calls() {
+	new_test_ext().execute_with(|| {
+		let org_id = org();
+		assert_ok!(Organizations::org_execute(Origin::signed(ADMIN), org_id, transfer(4, 200)));
+
+		let error = pallet_balances::Error::<Test>::InsufficientBalance.into();
+		System::assert_last_event(OrgEvent::OrgExecuted(org_id, ADMIN, Err(error)).into());
+	});
+}
+
+#[test]
+fn org_execute_filters_calls_by_role() {
+	new_test_ext().execute_with(|| {
+		let org_id = org();
+		let remark = Box::new(Call::System(frame_system::Call::remark { remark: vec![] }));
+
+		assert_noop!(
+			Organizations::org_execute(Origin::signed(REGISTRAR), org_id, remark.clone()),
+			Error::<Test>::CallFiltered
+		);
+		assert_noop!(
+			Organizations::org_execute(Origin::signed(AUDITOR), org_id, transfer(4, 1)),
+			Error::<Test>::CallFiltered
+		);
+		assert_noop!(
+			Organizations::org_execute(Origin::signed(4), org_id, transfer(4, 1)),
+			Error::<Test>::NotMember
+		);
+		assert_noop!(
+			Organizations::org_execute(Origin::signed(ADMIN), 7, remark.clone()),
+			Error::<Test>::OrgNotFound
+		);
+		assert_ok!(Organizations::org_execute(Origin::signed(ADMIN), org_id, remark));
+	});
+}
@@ -0,0 +1,646 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Storage deals between clients and storage providers.
+///
+/// A client escrows payment so that a number of registered providers keep a replica of some
+/// content, identified by its hash, for a fixed number of blocks. Providers prove they still hold
+/// the data by attesting periodically; anyone can settle a deal to stream the escrow to the
+/// providers. A provider that stops attesting defaults the deal and forfeits its bond to the
+/// client. It stays bound to its other deals, but cannot accept new ones until it re-registers.
+///
+/// Larger content can instead be erasure coded: each provider then holds one distinct shard and
+/// attests with a Merkle proof against the shard root recorded in the deal. Such a deal survives
+/// defaulting shard holders as long as enough of them are left to rebuild the content.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{
+		dispatch::DispatchResult,
+		pallet_prelude::*,
+		traits::{BalanceStatus, Currency, ReservableCurrency},
+		transactional,
+	};
+	use frame_system::pallet_prelude::*;
+	use scale_info::TypeInfo;
+	use sp_runtime::{
+		traits::{CheckedMul, Hash, Saturating, Zero},
+		SaturatedConversion,
+	};
+	use sp_std::prelude::*;
+
+	pub type DealId = u64;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	#[derive(Clone, Copy, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	pub enum DealStatus {
+		/// Waiting for enough providers to accept the deal.
+		Proposed,
+		/// Every replica is taken and payments are flowing to the providers.
+		Active,
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	#[scale_info(skip_type_params(T))]
+	pub struct Deal<T: Config> {
+		pub client: T::AccountId,
+		/// Hash of the content to be replicated.
+		pub content: T::Hash,
+		/// Size of the content in bytes, as declared by the client.
+		pub size: u64,
+		/// Number of providers that must hold a replica.
+		pub replication: u32,
+		/// Erasure coding of the content, if providers hold shards rather than full replicas.
+		pub shards: Option<ShardLayout<T::Hash>>,
+		/// Payment per block to each provider.
+		pub price_per_block: BalanceOf<T>,
+		pub duration: T::BlockNumber,
+		/// Block at which the deal was proposed.
+		pub proposed: T::BlockNumber,
+		pub providers: BoundedVec<T::AccountId, T::MaxReplication>,
+		/// Providers dropped for defaulting. They stay in `providers` so that the shard indices
+		/// of the others do not move.
+		pub dropped: BoundedVec<T::AccountId, T::MaxReplication>,
+		/// Part of the escrow still reserved on the client's account.
+		pub escrow: BalanceOf<T>,
+		pub status: DealStatus,
+		/// Block at which the deal became active.
+		pub start: Option<T::BlockNumber>,
+		/// Providers have been paid for every block before this one.
+		pub settled_until: T::BlockNumber,
+	}
+
+	/// Reconstruction metadata of erasure-coded content.
+	#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct ShardLayout<Hash> {
+		/// Merkle root over the hashes of all shards, in shard index order.
+		pub root: Hash,
+		/// Number of shards needed to rebuild the content.
+		pub data_shards: u32,
+		/// Number of shards produced, data and parity together.
+		pub total_shards: u32,
+		/// Size of every shard in bytes.
+		pub shard_size: u32,
+	}
+
+	#[derive(Clone, Encode, Decode, PartialEq, RuntimeDebug, TypeInfo)]
+	pub struct ProviderInfo<Balance> {
+		/// Amount still reserved from the registration bond, after any slashing.
+		pub bond: Balance,
+		/// Number of proposed or active deals the provider takes part in.
+		pub active_deals: u32,
+	}
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Currency used for deal escrow and provider bonds.
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// Bond reserved from a provider on registration and slashed if it defaults a deal.
+		#[pallet::constant]
+		type ProviderBond: Get<BalanceOf<Self>>;
+		/// Maximum number of replicas a single deal may ask for.
+		#[pallet::constant]
+		type MaxReplication: Get<u32>;
+		/// Shortest deal a client may propose.
+		#[pallet::constant]
+		type MinDealDuration: Get<Self::BlockNumber>;
+		/// Longest deal a client may propose.
+		#[pallet::constant]
+		type MaxDealDuration: Get<Self::BlockNumber>;
+		/// A provider whose latest attestation is older than this defaults the deal.
+		#[pallet::constant]
+		type AttestationPeriod: Get<Self::BlockNumber>;
+		/// A deal still waiting for providers this many blocks after its proposal may be
+		/// cancelled by anyone.
+		#[pallet::constant]
+		type MaxProposalAge: Get<Self::BlockNumber>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::storage]
+	#[pallet::getter(fn providers)]
+	pub type Providers<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, ProviderInfo<BalanceOf<T>>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn deals)]
+	pub type Deals<T: Config> = StorageMap<_, Blake2_128Concat, DealId, Deal<T>>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn next_deal_id)]
+	pub type NextDealId<T> = StorageValue<_, DealId, ValueQuery>;
+
+	/// Latest attestation block of each provider of an active deal.
+	#[pallet::storage]
+	#[pallet::getter(fn last_attestation)]
+	pub type Attestations<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		DealId,
+		Blake2_128Concat,
+		T::AccountId,
+		T::BlockNumber,
+	>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// [provider, bond]
+		ProviderRegistered(T::AccountId, BalanceOf<T>),
+		/// [provider]
+		ProviderUnregistered(T::AccountId),
+		/// [deal_id, client, content, escrow]
+		DealProposed(DealId, T::AccountId, T::Hash, BalanceOf<T>),
+		/// [deal_id]
+		DealCancelled(DealId),
+		/// [deal_id, provider]
+		DealAccepted(DealId, T::AccountId),
+		/// [deal_id, start]
+		DealActivated(DealId, T::BlockNumber),
+		/// [deal_id, provider, block]
+		StorageAttested(DealId, T::AccountId, T::BlockNumber),
+		/// [deal_id, provider, shard_index, block]
+		ShardAttested(DealId, T::AccountId, u32, T::BlockNumber),
+		/// [deal_id, paid]
+		DealSettled(DealId, BalanceOf<T>),
+		/// [deal_id, refunded]
+		DealExpired(DealId, BalanceOf<T>),
+		/// [deal_id, provider, slashed]
+		ProviderSlashed(DealId, T::AccountId, BalanceOf<T>),
+		/// [deal_id, refunded]
+		DealDefaulted(DealId, BalanceOf<T>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The account is already registered as a provider.
+		AlreadyProvider,
+		/// The account is not a registered provider.
+		NotProvider,
+		/// The provider's bond was slashed; it must re-register before accepting deals.
+		BondSlashed,
+		/// A provider cannot leave while it takes part in deals.
+		ProviderHasDeals,
+		/// Declared content size must be non-zero.
+		EmptyContent,
+		/// Replication must be between one and `MaxReplication`.
+		InvalidReplication,
+		/// Duration must be between `MinDealDuration` and `MaxDealDuration`.
+		InvalidDuration,
+		/// The escrow or the deal counter overflowed.
+		Overflow,
+		/// No deal with this id.
+		DealNotFound,
+		/// Only the client of the deal may do this.
+		NotDealClient,
+		/// The deal is no longer waiting for providers.
+		DealNotProposed,
+		/// The deal is not active.
+		DealNotActive,
+		/// The deal did not fill within `MaxProposalAge` and can only be cancelled.
+		ProposalExpired,
+		/// The client of a deal cannot also provide storage for it.
+		ClientCannotProvide,
+		/// The provider already accepted this deal.
+		AlreadyAccepted,
+		/// Every replica of the deal is already taken.
+		DealFull,
+		/// The account does not provide storage for this deal.
+		NotDealProvider,
+		/// Shard counts are inconsistent or too small to hold the content.
+		InvalidShardLayout,
+		/// Providers of a sharded deal must attest with a shard proof.
+		ShardProofRequired,
+		/// The deal holds full replicas, not shards.
+		NotShardedDeal,
+		/// The proof does not link the shard to the deal's Merkle root.
+		InvalidShardProof,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Register as a storage provider, reserving `ProviderBond`.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn register_provider(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(!Providers::<T>::contains_key(&who), Error::<T>::AlreadyProvider);
+
+			let bond = T::ProviderBond::get();
+			T::Currency::reserve(&who, bond)?;
+			Providers::<T>::insert(&who, ProviderInfo { bond, active_deals: 0 });
+
+			Self::deposit_event(Event::ProviderRegistered(who, bond));
+			Ok(())
+		}
+
+		/// Leave the provider set and get the bond back. Only possible outside of any deal.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn unregister_provider(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let info = Providers::<T>::get(&who).ok_or(Error::<T>::NotProvider)?;
+			ensure!(info.active_deals == 0, Error::<T>::ProviderHasDeals);
+
+			T::Currency::unreserve(&who, info.bond);
+			Providers::<T>::remove(&who);
+
+			Self::deposit_event(Event::ProviderUnregistered(who));
+			Ok(())
+		}
+
+		/// Propose a deal for `replication` replicas of `content` over `duration` blocks. The
+		/// whole price is reserved from the caller up front.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn propose_deal(
+			origin: OriginFor<T>,
+			content: T::Hash,
+			size: u64,
+			replication: u32,
+			price_per_block: BalanceOf<T>,
+			duration: T::BlockNumber,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::do_propose(who, content, size, replication, None, price_per_block, duration)
+		}
+
+		/// Propose a deal for the erasure-coded shards of `content`. Each of the
+		/// `layout.total_shards` providers holds one distinct shard, and any
+		/// `layout.data_shards` of them are enough to rebuild the content.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 3))]
+		pub fn propose_sharded_deal(
+			origin: OriginFor<T>,
+			content: T::Hash,
+			size: u64,
+			layout: ShardLayout<T::Hash>,
+			price_per_block: BalanceOf<T>,
+			duration: T::BlockNumber,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			ensure!(
+				(1..=layout.total_shards).contains(&layout.data_shards),
+				Error::<T>::InvalidShardLayout
+			);
+			ensure!(
+				(layout.shard_size as u64).saturating_mul(layout.data_shards as u64) >= size,
+				Error::<T>::InvalidShardLayout
+			);
+			let replication = layout.total_shards;
+			Self::do_propose(
+				who,
+				content,
+				size,
+				replication,
+				Some(layout),
+				price_per_block,
+				duration,
+			)
+		}
+
+		/// Withdraw a deal that has not started yet, releasing the escrow and the providers that
+		/// accepted it. Only the client may do this, until the proposal is older than
+		/// `MaxProposalAge`; then anyone may.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+			2 + T::MaxReplication::get() as Weight,
+			2 + T::MaxReplication::get() as Weight,
+		))]
+		pub fn cancel_deal(origin: OriginFor<T>, deal_id: DealId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let deal = Deals::<T>::get(deal_id).ok_or(Error::<T>::DealNotFound)?;
+			ensure!(deal.status == DealStatus::Proposed, Error::<T>::DealNotProposed);
+			ensure!(deal.client == who || Self::proposal_expired(&deal), Error::<T>::NotDealClient);
+
+			T::Currency::unreserve(&deal.client, deal.escrow);
+			Self::release_providers(&deal);
+			Deals::<T>::remove(deal_id);
+
+			Self::deposit_event(Event::DealCancelled(deal_id));
+			Ok(())
+		}
+
+		/// Take one replica of a proposed deal. The deal becomes active once all replicas are
+		/// taken.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+			2,
+			2 + T::MaxReplication::get() as Weight,
+		))]
+		pub fn accept_deal(origin: OriginFor<T>, deal_id: DealId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let info = Providers::<T>::get(&who).ok_or(Error::<T>::NotProvider)?;
+			ensure!(info.bond >= T::ProviderBond::get(), Error::<T>::BondSlashed);
+
+			Deals::<T>::try_mutate(deal_id, |maybe_deal| -> DispatchResult {
+				let deal = maybe_deal.as_mut().ok_or(Error::<T>::DealNotFound)?;
+				ensure!(deal.status == DealStatus::Proposed, Error::<T>::DealNotProposed);
+				ensure!(!Self::proposal_expired(deal), Error::<T>::ProposalExpired);
+				ensure!(deal.client != who, Error::<T>::ClientCannotProvide);
+				ensure!(!deal.providers.contains(&who), Error::<T>::AlreadyAccepted);
+				ensure!((deal.providers.len() as u32) < deal.replication, Error::<T>::DealFull);
+				deal.providers.try_push(who.clone()).map_err(|_| Error::<T>::DealFull)?;
+
+				Providers::<T>::mutate(&who, |info| {
+					if let Some(info) = info {
+						info.active_deals = info.active_deals.saturating_add(1);
+					}
+				});
+				Self::deposit_event(Event::DealAccepted(deal_id, who));
+
+				if deal.providers.len() as u32 == deal.replication {
+					let now = <frame_system::Pallet<T>>::block_number();
+					deal.status = DealStatus::Active;
+					deal.start = Some(now);
+					deal.settled_until = now;
+					// Nobody has to attest for the time spent waiting for the other providers.
+					for provider in deal.providers.iter() {
+						Attestations::<T>::insert(deal_id, provider, now);
+					}
+					Self::deposit_event(Event::DealActivated(deal_id, now));
+				}
+				Ok(())
+			})
+		}
+
+		/// Attest that the caller still holds its replica of an active deal.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn attest(origin: OriginFor<T>, deal_id: DealId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let deal = Deals::<T>::get(deal_id).ok_or(Error::<T>::DealNotFound)?;
+			ensure!(deal.status == DealStatus::Active, Error::<T>::DealNotActive);
+			ensure!(deal.providers.contains(&who), Error::<T>::NotDealProvider);
+			ensure!(deal.shards.is_none(), Error::<T>::ShardProofRequired);
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			Attestations::<T>::insert(deal_id, &who, now);
+
+			Self::deposit_event(Event::StorageAttested(deal_id, who, now));
+			Ok(())
+		}
+
+		/// Attest that the caller holds its shard of an active sharded deal, by proving the
+		/// shard's hash is the leaf at the caller's shard index under the deal's Merkle root.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 1))]
+		pub fn attest_shard(
+			origin: OriginFor<T>,
+			deal_id: DealId,
+			shard_hash: T::Hash,
+			proof: Vec<T::Hash>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let deal = Deals::<T>::get(deal_id).ok_or(Error::<T>::DealNotFound)?;
+			ensure!(deal.status == DealStatus::Active, Error::<T>::DealNotActive);
+			let layout = deal.shards.as_ref().ok_or(Error::<T>::NotShardedDeal)?;
+			let index = Self::shard_index(&deal, &who).ok_or(Error::<T>::NotDealProvider)?;
+			ensure!(
+				Self::verify_shard(&layout.root, shard_hash, index, layout.total_shards, &proof),
+				Error::<T>::InvalidShardProof
+			);
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			Attestations::<T>::insert(deal_id, &who, now);
+
+			Self::deposit_event(Event::ShardAttested(deal_id, who, index, now));
+			Ok(())
+		}
+
+		/// Pay the providers of an active deal for the blocks elapsed since the last settlement.
+		///
+		/// Expires the deal once its duration is over. Providers that have not attested within
+		/// `AttestationPeriod` are not paid for the window and their bonds go to the client.
+		/// A sharded deal then drops them and carries on while at least `data_shards` holders
+		/// are left; their share of the escrow is refunded when the deal ends. Otherwise the
+		/// deal defaults and the unused escrow goes back to the client. A deal that expires or
+		/// defaults is removed, leaving `DealExpired` or `DealDefaulted` as its record. Anyone
+		/// may call this.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(
+			2 + 3 * T::MaxReplication::get() as Weight,
+			2 + 3 * T::MaxReplication::get() as Weight,
+		))]
+		#[transactional]
+		pub fn settle(origin: OriginFor<T>, deal_id: DealId) -> DispatchResult {
+			let _ = ensure_signed(origin)?;
+			let mut deal = Deals::<T>::get(deal_id).ok_or(Error::<T>::DealNotFound)?;
+			ensure!(deal.status == DealStatus::Active, Error::<T>::DealNotActive);
+
+			let now = <frame_system::Pallet<T>>::block_number();
+			let end = deal.start.unwrap_or(deal.settled_until).saturating_add(deal.duration);
+			let until = now.min(end);
+			let period = T::AttestationPeriod::get();
+			let defaulters: Vec<T::AccountId> = Self::holders(&deal)
+				.filter(|provider| {
+					let last =
+						Attestations::<T>::get(deal_id, provider).unwrap_or(deal.settled_until);
+					until.saturating_sub(last) > period
+				})
+				.cloned()
+				.collect();
+
+			let share =
+				Self::price_of(deal.price_per_block, until.saturating_sub(deal.settled_until), 1)
+					.ok_or(Error::<T>::Overflow)?;
+			let mut paid: BalanceOf<T> = Zero::zero();
+			for provider in Self::holders(&deal).filter(|p| !defaulters.contains(p)) {
+				let amount = share.min(deal.escrow.saturating_sub(paid));
+				let not_moved = T::Currency::repatriate_reserved(
+					&deal.client,
+					provider,
+					amount,
+					BalanceStatus::Free,
+				)?;
+				paid = paid.saturating_add(amount.saturating_sub(not_moved));
+			}
+			deal.escrow = deal.escrow.saturating_sub(paid);
+			deal.settled_until = until;
+			Self::deposit_event(Event::DealSettled(deal_id, paid));
+
+			for defaulter in defaulters.iter() {
+				let slashed = Self::slash(defaulter, &deal.client)?;
+				Self::release(defaulter);
+				Attestations::<T>::remove(deal_id, defaulter);
+				deal.dropped.try_push(defaulter.clone()).map_err(|_| Error::<T>::Overflow)?;
+				Self::deposit_event(Event::ProviderSlashed(deal_id, defaulter.clone(), slashed));
+			}
+			// Only erasure coding lets the content outlive a defaulting provider.
+			let holders = Self::holders(&deal).count() as u32;
+			let defaulted = !defaulters.is_empty() &&
+				deal.shards.as_ref().map_or(true, |layout| holders < layout.data_shards);
+
+			if defaulted {
+				T::Currency::unreserve(&deal.client, deal.escrow);
+				Self::close(deal_id, &deal);
+				Self::deposit_event(Event::DealDefaulted(deal_id, deal.escrow));
+			} else if until == end {
+				T::Currency::unreserve(&deal.client, deal.escrow);
+				Self::close(deal_id, &deal);
+				Self::deposit_event(Event::DealExpired(deal_id, deal.escrow));
+			} else {
+				Deals::<T>::insert(deal_id, deal);
+			}
+			Ok(())
+		}
+	}
+
+	impl<T: Config> Pallet<T> {
+		fn do_propose(
+			who: T::AccountId,
+			content: T::Hash,
+			size: u64,
+			replication: u32,
+			shards: Option<ShardLayout<T::Hash>>,
+			price_per_block: BalanceOf<T>,
+			duration: T::BlockNumber,
+		) -> DispatchResult {
+			ensure!(size > 0, Error::<T>::EmptyContent);
+			ensure!(
+				(1..=T::MaxReplication::get()).contains(&replication),
+				Error::<T>::InvalidReplication
+			);
+			ensure!(
+				(T::MinDealDuration::get()..=T::MaxDealDuration::get()).contains(&duration),
+				Error::<T>::InvalidDuration
+			);
+
+			let escrow = Self::price_of(price_per_block, duration, replication)
+				.ok_or(Error::<T>::Overflow)?;
+			let deal_id = NextDealId::<T>::get();
+			let next_id = deal_id.checked_add(1).ok_or(Error::<T>::Overflow)?;
+			T::Currency::reserve(&who, escrow)?;
+
+			Deals::<T>::insert(
+				deal_id,
+				Deal {
+					client: who.clone(),
+					content,
+					size,
+					replication,
+					shards,
+					price_per_block,
+					duration,
+					proposed: <frame_system::Pallet<T>>::block_number(),
+					providers: Default::default(),
+					dropped: Default::default(),
+					escrow,
+					status: DealStatus::Proposed,
+					start: None,
+					settled_until: Zero::zero(),
+				},
+			);
+			NextDealId::<T>::put(next_id);
+
+			Self::deposit_event(Event::DealProposed(deal_id, who, content, escrow));
+			Ok(())
+		}
+
+		/// Index of the shard held by `provider`, which is the order in which providers accepted
+		/// the deal. Dropped holders no longer have one.
+		pub fn shard_index(deal: &Deal<T>, provider: &T::AccountId) -> Option<u32> {
+			if deal.dropped.contains(provider) {
+				return None
+			}
+			deal.providers.iter().position(|p| p == provider).map(|i| i as u32)
+		}
+
+		/// Whether a proposed deal has waited longer than `MaxProposalAge` for its providers.
+		fn proposal_expired(deal: &Deal<T>) -> bool {
+			let now = <frame_system::Pallet<T>>::block_number();
+			now.saturating_sub(deal.proposed) > T::MaxProposalAge::get()
+		}
+
+		/// Providers of the deal that have not been dropped.
+		fn holders(deal: &Deal<T>) -> impl Iterator<Item = &T::AccountId> {
+			deal.providers.iter().filter(move |p| !deal.dropped.contains(p))
+		}
+
+		/// Check `proof` links the hash of shard `index` to the Merkle `root` of a layout with
+		/// `total_shards` leaves.
+		///
+		/// Leaves are padded with the default hash up to the next power of two, and each inner
+		/// node is the hash of the encoded `(left, right)` pair.
+		pub fn verify_shard(
+			root: &T::Hash,
+			leaf: T::Hash,
+			index: u32,
+			total_shards: u32,
+			proof: &[T::Hash],
+		) -> bool {
+			let depth = 32 - total_shards.saturating_sub(1).leading_zeros();
+			if index >= total_shards || proof.len() as u32 != depth {
+				return false;
+			}
+			let (node, _) = proof.iter().fold((leaf, index), |(node, index), sibling| {
+				let parent = if index % 2 == 0 {
+					T::Hashing::hash_of(&(node, *sibling))
+				} else {
+					T::Hashing::hash_of(&(*sibling, node))
+				};
+				(parent, index / 2)
+			});
+			&node == root
+		}
+
+		/// Total price of `replicas` replicas held for `blocks` blocks.
+		pub fn price_of(
+			price_per_block: BalanceOf<T>,
+			blocks: T::BlockNumber,
+			replicas: u32,
+		) -> Option<BalanceOf<T>> {
+			let blocks: BalanceOf<T> = blocks.saturated_into::<u32>().into();
+			price_per_block.checked_mul(&blocks)?.checked_mul(&replicas.into())
+		}
+
+		/// Move what is left of the bond of `provider` to `beneficiary`. The registration is
+		/// kept, so the provider still counts its other deals and cannot leave before they end.
+		fn slash(
+			provider: &T::AccountId,
+			beneficiary: &T::AccountId,
+		) -> Result<BalanceOf<T>, DispatchError> {
+			Providers::<T>::try_mutate(provider, |maybe_info| -> Result<_, DispatchError> {
+				let info = match maybe_info {
+					Some(info) => info,
+					None => return Ok(Zero::zero()),
+				};
+				let not_moved = T::Currency::repatriate_reserved(
+					provider,
+					beneficiary,
+					info.bond,
+					BalanceStatus::Free,
+				)?;
+				let slashed = info.bond.saturating_sub(not_moved);
+				info.bond = info.bond.saturating_sub(slashed);
+				Ok(slashed)
+			})
+		}
+
+		fn release(provider: &T::AccountId) {
+			Providers::<T>::mutate(provider, |info| {
+				if let Some(info) = info {
+					info.active_deals = info.active_deals.saturating_sub(1);
+				}
+			});
+		}
+
+		fn release_providers(deal: &Deal<T>) {
+			for provider in Self::holders(deal) {
+				Self::release(provider);
+			}
+		}
+
+		/// Release the providers of a deal that ended and drop it from storage.
+		fn close(deal_id: DealId, deal: &Deal<T>) {
+			Self::release_providers(deal);
+			Attestations::<T>::remove_prefix(deal_id, None);
+			Deals::<T>::remove(deal_id);
+		}
+	}
+}
@@ -0,0 +1,422 @@
+use crate::{mock::*, DealStatus, Error, ProviderInfo, ShardLayout};
+use frame_support::{assert_noop, assert_ok};
+use sp_core::H256;
+use sp_runtime::traits::{BlakeTwo256, Hash};
+
+fn content() -> H256 {
+	H256::repeat_byte(1)
+}
+
+/// Registers both providers and opens a two-replica deal paying 2 per block for 10 blocks.
+fn active_deal() -> u64 {
+	assert_ok!(StorageDeal::register_provider(Origin::signed(ALICE)));
+	assert_ok!(StorageDeal::register_provider(Origin::signed(BOB)));
+	assert_ok!(StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 2, 2, 10));
+	assert_ok!(StorageDeal::accept_deal(Origin::signed(ALICE), 0));
+	assert_ok!(StorageDeal::accept_deal(Origin::signed(BOB), 0));
+	0
+}
+
+#[test]
+fn propose_deal_reserves_escrow() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 2, 2, 10));
+		assert_eq!(Balances::reserved_balance(CLIENT), 40);
+
+		let deal = StorageDeal::deals(0).expect("deal was proposed");
+		assert_eq!(deal.status, DealStatus::Proposed);
+		assert_eq!(deal.escrow, 40);
+		assert_eq!(StorageDeal::next_deal_id(), 1);
+	});
+}
+
+#[test]
+fn propose_deal_checks_bounds() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 0, 1, 1, 10),
+			Error::<Test>::EmptyContent
+		);
+		assert_noop!(
+			StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 4, 1, 10),
+			Error::<Test>::InvalidReplication
+		);
+		assert_noop!(
+			StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 1, 1, 5),
+			Error::<Test>::InvalidDuration
+		);
+	});
+}
+
+#[test]
+fn cancel_deal_refunds_client() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StorageDeal::register_provider(Origin::signed(ALICE)));
+		assert_ok!(StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 2, 2, 10));
+		assert_ok!(StorageDeal::accept_deal(Origin::signed(ALICE), 0));
+
+		assert_noop!(
+			StorageDeal::cancel_deal(Origin::signed(ALICE), 0),
+			Error::<Test>::NotDealClient
+		);
+		assert_ok!(StorageDeal::cancel_deal(Origin::signed(CLIENT), 0));
+
+		assert_eq!(Balances::reserved_balance(CLIENT), 0);
+		assert_eq!(StorageDeal::deals(0), None);
+		assert_eq!(StorageDeal::providers(ALICE).unwrap().active_deals, 0);
+	});
+}
+
+#[test]
+fn provider_can_leave_a_deal_that_never_fills() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StorageDeal::register_provider(Origin::signed(ALICE)));
+		assert_ok!(StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 3, 2, 10));
+		assert_ok!(StorageDeal::accept_deal(Origin::signed(ALICE), 0));
+
+		// Until the proposal is too old, only the client may withdraw it.
+		System::set_block_number(21);
+		assert_noop!(
+			StorageDeal::cancel_deal(Origin::signed(ALICE), 0),
+			Error::<Test>::NotDealClient
+		);
+
+		System::set_block_number(22);
+		assert_ok!(StorageDeal::register_provider(Origin::signed(BOB)));
+		assert_noop!(
+			StorageDeal::accept_deal(Origin::signed(BOB), 0),
+			Error::<Test>::ProposalExpired
+		);
+		assert_ok!(StorageDeal::cancel_deal(Origin::signed(ALICE), 0));
+
+		assert_eq!(StorageDeal::deals(0), None);
+		assert_eq!(Balances::reserved_balance(CLIENT), 0);
+		assert_eq!(Balances::free_balance(CLIENT), 10_000);
+		assert_ok!(StorageDeal::unregister_provider(Origin::signed(ALICE)));
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+		assert_eq!(Balances::free_balance(ALICE), 1_000);
+	});
+}
+
+#[test]
+fn accept_deal_requires_registered_provider() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 1, 1, 10));
+		assert_noop!(
+			StorageDeal::accept_deal(Origin::signed(ALICE), 0),
+			Error::<Test>::NotProvider
+		);
+
+		assert_ok!(StorageDeal::register_provider(Origin::signed(CLIENT)));
+		assert_noop!(
+			StorageDeal::accept_deal(Origin::signed(CLIENT), 0),
+			Error::<Test>::ClientCannotProvide
+		);
+	});
+}
+
+#[test]
+fn deal_activates_once_all_replicas_are_taken() {
+	new_test_ext().execute_with(|| {
+		let deal_id = active_deal();
+		let deal = StorageDeal::deals(deal_id).unwrap();
+		assert_eq!(deal.status, DealStatus::Active);
+		assert_eq!(deal.start, Some(1));
+
+		assert_noop!(
+			StorageDeal::accept_deal(Origin::signed(ALICE), deal_id),
+			Error::<Test>::DealNotProposed
+		);
+		assert_noop!(
+			StorageDeal::unregister_provider(Origin::signed(ALICE)),
+			Error::<Test>::ProviderHasDeals
+		);
+	});
+}
+
+#[test]
+fn settle_pays_providers_until_expiry() {
+	new_test_ext().execute_with(|| {
+		let deal_id = active_deal();
+
+		System::set_block_number(6);
+		assert_ok!(StorageDeal::attest(Origin::signed(ALICE), deal_id));
+		assert_ok!(StorageDeal::attest(Origin::signed(BOB), deal_id));
+		assert_ok!(StorageDeal::settle(Origin::signed(CLIENT), deal_id));
+		assert_eq!(Balances::free_balance(ALICE), 910);
+		assert_eq!(Balances::reserved_balance(CLIENT), 20);
+
+		System::set_block_number(15);
+		assert_ok!(StorageDeal::attest(Origin::signed(ALICE), deal_id));
+		assert_ok!(StorageDeal::attest(Origin::signed(BOB), deal_id));
+		assert_ok!(StorageDeal::settle(Origin::signed(CLIENT), deal_id));
+
+		System::assert_last_event(Event::StorageDeal(crate::Event::DealExpired(deal_id, 0)));
+		assert_eq!(StorageDeal::deals(deal_id), None);
+		assert_eq!(Balances::free_balance(ALICE), 920);
+		assert_eq!(Balances::free_balance(BOB), 920);
+		assert_eq!(Balances::reserved_balance(CLIENT), 0);
+		assert_eq!(Balances::free_balance(CLIENT), 9_960);
+
+		// Both providers are free to leave again.
+		assert_ok!(StorageDeal::unregister_provider(Origin::signed(ALICE)));
+		assert_eq!(Balances::reserved_balance(ALICE), 0);
+	});
+}
+
+#[test]
+fn missing_attestation_defaults_the_deal() {
+	new_test_ext().execute_with(|| {
+		let deal_id = active_deal();
+
+		System::set_block_number(8);
+		assert_ok!(StorageDeal::attest(Origin::signed(ALICE), deal_id));
+		assert_ok!(StorageDeal::settle(Origin::signed(ALICE), deal_id));
+
+		System::assert_last_event(Event::StorageDeal(crate::Event::DealDefaulted(deal_id, 26)));
+		assert_eq!(StorageDeal::deals(deal_id), None);
+		// Alice is paid for the seven blocks she kept attesting.
+		assert_eq!(Balances::free_balance(ALICE), 914);
+		// Bob's bond and the unused escrow end up with the client.
+		assert_eq!(Balances::reserved_balance(BOB), 0);
+		assert_eq!(Balances::free_balance(BOB), 900);
+		assert_eq!(StorageDeal::providers(BOB), Some(ProviderInfo { bond: 0, active_deals: 0 }));
+		assert_eq!(Balances::reserved_balance(CLIENT), 0);
+		assert_eq!(Balances::free_balance(CLIENT), 10_086);
+		assert_eq!(Balances::reserved_balance(ALICE), 100);
+
+		assert_noop!(
+			StorageDeal::settle(Origin::signed(ALICE), deal_id),
+			Error::<Test>::DealNotFound
+		);
+	});
+}
+
+#[test]
+fn every_stale_provider_is_slashed() {
+	new_test_ext().execute_with(|| {
+		let deal_id = active_deal();
+
+		System::set_block_number(8);
+		assert_ok!(StorageDeal::settle(Origin::signed(CLIENT), deal_id));
+
+		System::assert_last_event(Event::StorageDeal(crate::Event::DealDefaulted(deal_id, 40)));
+		// Neither provider is paid, and both bonds go to the client with the whole escrow.
+		for provider in [ALICE, BOB] {
+			assert_eq!(Balances::free_balance(provider), 900);
+			assert_eq!(Balances::reserved_balance(provider), 0);
+		}
+		assert_eq!(Balances::reserved_balance(CLIENT), 0);
+		assert_eq!(Balances::free_balance(CLIENT), 10_200);
+	});
+}
+
+#[test]
+fn slashed_provider_stays_bound_to_its_other_deals() {
+	new_test_ext().execute_with(|| {
+		let deal_id = active_deal();
+		assert_ok!(StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 1, 2, 10));
+		assert_ok!(StorageDeal::accept_deal(Origin::signed(BOB), 1));
+
+		System::set_block_number(8);
+		assert_ok!(StorageDeal::attest(Origin::signed(ALICE), deal_id));
+		assert_ok!(StorageDeal::attest(Origin::signed(BOB), 1));
+		assert_ok!(StorageDeal::settle(Origin::signed(CLIENT), deal_id));
+		assert_eq!(StorageDeal::providers(BOB), Some(ProviderInfo { bond: 0, active_deals: 1 }));
+
+		// Bob can neither walk away from deal 1 nor reset his registration.
+		assert_noop!(
+			StorageDeal::unregister_provider(Origin::signed(BOB)),
+			Error::<Test>::ProviderHasDeals
+		);
+		assert_noop!(
+			StorageDeal::register_provider(Origin::signed(BOB)),
+			Error::<Test>::AlreadyProvider
+		);
+		assert_ok!(StorageDeal::propose_deal(Origin::signed(CLIENT), content(), 128, 1, 2, 10));
+		assert_noop!(StorageDeal::accept_deal(Origin::signed(BOB), 2), Error::<Test>::BondSlashed);
+
+		// Once deal 1 is over he may leave and register with a fresh bond.
+		System::set_block_number(11);
+		assert_ok!(StorageDeal::attest(Origin::signed(BOB), 1));
+		assert_ok!(StorageDeal::settle(Origin::signed(CLIENT), 1));
+		assert_ok!(StorageDeal::unregister_provider(Origin::signed(BOB)));
+		assert_ok!(StorageDeal::register_provider(Origin::signed(BOB)));
+		assert_ok!(StorageDeal::accept_deal(Origin::signed(BOB), 2));
+	});
+}
+
+#[test]
+fn only_deal_providers_can_attest() {
+	new_test_ext().execute_with(|| {
+		let deal_id = active_deal();
+		assert_noop!(
+			StorageDeal::attest(Origin::signed(CLIENT), deal_id),
+			Error::<Test>::NotDealProvider
+		);
+		assert_noop!(StorageDeal::attest(Origin::signed(ALICE), 7), Error::<Test>::DealNotFound);
+	});
+}
+
+/// Merkle tree over three shards, padded to four leaves.
+fn shard_tree() -> (H256, [H256; 4]) {
+	let leaves =
+		[H256::repeat_byte(10), H256::repeat_byte(11), H256::repeat_byte(12), H256::default()];
+	let left = BlakeTwo256::hash_of(&(leaves[0], leaves[1]));
+	let right = BlakeTwo256::hash_of(&(leaves[2], leaves[3]));
+	(BlakeTwo256::hash_of(&(left, right)), leaves)
+}
+
+fn layout(root: H256) -> ShardLayout<H256> {
+	ShardLayout { root, data_shards: 2, total_shards: 3, shard_size: 64 }
+}
+
+#[test]
+fn sharded_deal_checks_layout() {
+	new_test_ext().execute_with(|| {
+		let (root, _) = shard_tree();
+		let mut bad = layout(root);
+		bad.data_shards = 4;
+		assert_noop!(
+			StorageDeal::propose_sharded_deal(Origin::signed(CLIENT), content(), 128, bad, 1, 10),
+			Error::<Test>::InvalidShardLayout
+		);
+		// Two 64 byte data shards cannot hold 129 bytes.
+		assert_noop!(
+			StorageDeal::propose_sharded_deal(
+				Origin::signed(CLIENT),
+				content(),
+				129,
+				layout(root),
+				1,
+				10
+			),
+			Error::<Test>::InvalidShardLayout
+		);
+
+		assert_ok!(StorageDeal::propose_sharded_deal(
+			Origin::signed(CLIENT),
+			content(),
+			128,
+			layout(root),
+			1,
+			10
+		));
+		let deal = StorageDeal::deals(0).unwrap();
+		assert_eq!(deal.replication, 3);
+		assert_eq!(deal.shards, Some(layout(root)));
+		assert_eq!(Balances::reserved_balance(CLIENT), 30);
+	});
+}
+
+#[test]
+fn shard_holders_attest_with_merkle_proofs() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::transfer(Origin::signed(CLIENT), 4, 1_000));
+		let (root, leaves) = shard_tree();
+		assert_ok!(StorageDeal::propose_sharded_deal(
+			Origin::signed(CLIENT),
+			content(),
+			128,
+			layout(root),
+			1,
+			10
+		));
+		for provider in [ALICE, BOB, 4] {
+			assert_ok!(StorageDeal::register_provider(Origin::signed(provider)));
+			assert_ok!(StorageDeal::accept_deal(Origin::signed(provider), 0));
+		}
+		let deal = StorageDeal::deals(0).unwrap();
+		assert_eq!(deal.status, DealStatus::Active);
+		assert_eq!(StorageDeal::shard_index(&deal, &BOB), Some(1));
+
+		// Bob holds shard 1, whose sibling is shard 0 and whose uncle is the right subtree.
+		let right = BlakeTwo256::hash_of(&(leaves[2], leaves[3]));
+		System::set_block_number(4);
+		assert_ok!(StorageDeal::attest_shard(
+			Origin::signed(BOB),
+			0,
+			leaves[1],
+			vec![leaves[0], right]
+		));
+		assert_eq!(StorageDeal::last_attestation(0, BOB), Some(4));
+
+		// The same proof does not hold at Alice's index.
+		assert_noop!(
+			StorageDeal::attest_shard(Origin::signed(ALICE), 0, leaves[1], vec![leaves[0], right]),
+			Error::<Test>::InvalidShardProof
+		);
+		assert_noop!(
+			StorageDeal::attest(Origin::signed(BOB), 0),
+			Error::<Test>::ShardProofRequired
+		);
+	});
+}
+
+#[test]
+fn sharded_deal_survives_defaults_while_recoverable() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Balances::transfer(Origin::signed(CLIENT), 4, 1_000));
+		let (root, leaves) = shard_tree();
+		let left = BlakeTwo256::hash_of(&(leaves[0], leaves[1]));
+		let right = BlakeTwo256::hash_of(&(leaves[2], leaves[3]));
+		assert_ok!(StorageDeal::propose_sharded_deal(
+			Origin::signed(CLIENT),
+			content(),
+			128,
+			layout(root),
+			1,
+			20
+		));
+		for provider in [ALICE, BOB, 4] {
+			assert_ok!(StorageDeal::register_provider(Origin::signed(provider)));
+			assert_ok!(StorageDeal::accept_deal(Origin::signed(provider), 0));
+		}
+
+		// Bob stops attesting, but shards 0 and 2 still rebuild the content.
+		System::set_block_number(7);
+		assert_ok!(StorageDeal::attest_shard(
+			Origin::signed(ALICE),
+			0,
+			leaves[0],
+			vec![leaves[1], right]
+		));
+		assert_ok!(StorageDeal::attest_shard(
+			Origin::signed(4),
+			0,
+			leaves[2],
+			vec![leaves[3], left]
+		));
+		assert_ok!(StorageDeal::settle(Origin::signed(CLIENT), 0));
+
+		let deal = StorageDeal::deals(0).unwrap();
+		assert_eq!(deal.status, DealStatus::Active);
+		assert_eq!(deal.dropped.to_vec(), vec![BOB]);
+		assert_eq!(StorageDeal::providers(BOB), Some(ProviderInfo { bond: 0, active_deals: 0 }));
+		assert_eq!(Balances::free_balance(BOB), 900);
+		assert_eq!(Balances::free_balance(ALICE), 906);
+		assert_eq!(Balances::reserved_balance(CLIENT), 48);
+		assert_noop!(
+			StorageDeal::attest_shard(Origin::signed(BOB), 0, leaves[1], vec![leaves[0], right]),
+			Error::<Test>::NotDealProvider
+		);
+
+		// Losing a second shard leaves fewer than `data_shards` holders.
+		System::set_block_number(13);
+		assert_ok!(StorageDeal::attest_shard(
+			Origin::signed(ALICE),
+			0,
+			leaves[0],
+			vec![leaves[1], right]
+		));
+		assert_ok!(StorageDeal::settle(Origin::signed(CLIENT), 0));
+
+		System::assert_last_event(Event::StorageDeal(crate::Event::DealDefaulted(0, 42)));
+		assert_eq!(StorageDeal::deals(0), None);
+		assert_eq!(Balances::free_balance(ALICE), 912);
+		assert_eq!(Balances::free_balance(4), 906);
+		assert_eq!(Balances::reserved_balance(CLIENT), 0);
+		assert_eq!(Balances::free_balance(CLIENT), 9_182);
+		assert_eq!(StorageDeal::providers(ALICE).unwrap().active_deals, 0);
+	});
+}
@@ -0,0 +1,162 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+/// Authority set management for the PoA chain.
+///
+/// Governance adds and removes validators at any time, but the change only reaches Aura and
+/// GRANDPA at the next session boundary: this pallet is the `SessionManager` of
+/// `pallet-session`, which hands the new set to the consensus pallets together with the session
+/// keys every validator registered through `Session::set_keys`. Only validators that have
+/// registered keys can join, so consensus never gets a member without them.
+pub use pallet::*;
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod tests;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use frame_support::{dispatch::DispatchResult, pallet_prelude::*};
+	use frame_system::pallet_prelude::*;
+	use sp_runtime::traits::Convert;
+	use sp_std::prelude::*;
+
+	/// Configure the pallet by specifying the parameters and types on which it depends.
+	#[pallet::config]
+	pub trait Config: frame_system::Config + pallet_session::Config {
+		/// Because this pallet emits events, it depends on the runtime's definition of an event.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Origin allowed to add and remove validators.
+		type AddRemoveOrigin: EnsureOrigin<Self::Origin>;
+		/// The set can never shrink below this many validators.
+		#[pallet::constant]
+		type MinAuthorities: Get<u32>;
+		/// The set can never grow beyond this many validators. Should match the limit of the
+		/// consensus pallets.
+		#[pallet::constant]
+		type MaxAuthorities: Get<u32>;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	pub struct Pallet<T>(_);
+
+	/// Validators for the next session.
+	#[pallet::storage]
+	#[pallet::getter(fn validators)]
+	pub type Validators<T: Config> =
+		StorageValue<_, BoundedVec<T::AccountId, T::MaxAuthorities>, ValueQuery>;
+
+	/// Whether `Validators` changed since it was last handed to the session pallet.
+	#[pallet::storage]
+	pub type SetChanged<T> = StorageValue<_, bool, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub validators: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { validators: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			let mut validators = self.validators.clone();
+			validators.sort();
+			validators.dedup();
+			assert_eq!(validators.len(), self.validators.len(), "Duplicate genesis validators");
+			let validators: BoundedVec<_, T::MaxAuthorities> =
+				self.validators.clone().try_into().expect("Too many genesis validators");
+			Validators::<T>::put(validators);
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The validator joins the set from the next session on. [validator]
+		ValidatorAdded(T::AccountId),
+		/// The validator leaves the set from the next session on. [validator]
+		ValidatorRemoved(T::AccountId),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The account is already a validator.
+		AlreadyValidator,
+		/// The account is not a validator.
+		NotValidator,
+		/// Removing the validator would leave fewer than `MinAuthorities`.
+		TooFewValidators,
+		/// The set already holds `MaxAuthorities` validators.
+		TooManyValidators,
+		/// The account has not registered session keys with `Session::set_keys`.
+		NoSessionKeys,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Add a validator. It must have set its session keys, and starts authoring once the
+		/// session after next begins.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(2, 2))]
+		pub fn add_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+			let id = <T as pallet_session::Config>::ValidatorIdOf::convert(validator.clone())
+				.ok_or(Error::<T>::NoSessionKeys)?;
+			ensure!(pallet_session::NextKeys::<T>::contains_key(&id), Error::<T>::NoSessionKeys);
+			Validators::<T>::try_mutate(|validators| -> DispatchResult {
+				ensure!(!validators.contains(&validator), Error::<T>::AlreadyValidator);
+				validators
+					.try_push(validator.clone())
+					.map_err(|_| Error::<T>::TooManyValidators)?;
+				Ok(())
+			})?;
+			SetChanged::<T>::put(true);
+
+			Self::deposit_event(Event::ValidatorAdded(validator));
+			Ok(())
+		}
+
+		/// Remove a validator from the set used by upcoming sessions.
+		#[pallet::weight(10_000 + T::DbWeight::get().reads_writes(1, 2))]
+		pub fn remove_validator(origin: OriginFor<T>, validator: T::AccountId) -> DispatchResult {
+			T::AddRemoveOrigin::ensure_origin(origin)?;
+			Validators::<T>::try_mutate(|validators| -> DispatchResult {
+				let index = validators
+					.iter()
+					.position(|v| v == &validator)
+					.ok_or(Error::<T>::NotValidator)?;
+				ensure!(
+					validators.len() as u32 > T::MinAuthorities::get(),
+					Error::<T>::TooFewValidators
+				);
+				validators.remove(index);
+				Ok(())
+			})?;
+			SetChanged::<T>::put(true);
+
+			Self::deposit_event(Event::ValidatorRemoved(validator));
+			Ok(())
+		}
+	}
+
+	impl<T: Config> pallet_session::SessionManager<T::AccountId> for Pallet<T> {
+		fn new_session(_new_index: u32) -> Option<Vec<T::AccountId>> {
+			if SetChanged::<T>::take() {
+				Some(Validators::<T>::get().into_inner())
+			} else {
+				None
+			}
+		}
+
+		fn end_session(_end_index: u32) {}
+
+		fn start_session(_start_index: u32) {}
+	}
+}
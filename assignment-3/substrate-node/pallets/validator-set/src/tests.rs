@@ -0,0 +1,98 @@
+use crate::{mock::*, Error};
+use frame_support::{assert_noop, assert_ok, traits::OnInitialize};
+use pallet_session::SessionManager;
+use sp_runtime::DispatchError;
+
+/// Move to the next block, which ends the session since `Period` is one block.
+fn next_session() {
+	let block = System::block_number() + 1;
+	System::set_block_number(block);
+	Session::on_initialize(block);
+}
+
+#[test]
+fn genesis_validators_are_set() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![1, 2]);
+		// Nothing changed since genesis, so the session pallet keeps its own set.
+		assert_eq!(ValidatorSet::new_session(1), None);
+	});
+}
+
+#[test]
+fn changes_apply_at_the_next_session() {
+	new_test_ext().execute_with(|| {
+		set_keys(3);
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 1));
+		assert_eq!(ValidatorSet::validators().into_inner(), vec![2, 3]);
+		assert_eq!(Session::validators(), vec![1, 2]);
+
+		// The first boundary queues the new set together with its keys...
+		next_session();
+		assert_eq!(Session::current_index(), 1);
+		assert_eq!(Session::validators(), vec![1, 2]);
+		let queued: Vec<u64> = Session::queued_keys().into_iter().map(|(v, _)| v).collect();
+		assert_eq!(queued, vec![2, 3]);
+
+		// ...and the second one hands it to consensus.
+		next_session();
+		assert_eq!(Session::validators(), vec![2, 3]);
+		next_session();
+		assert_eq!(Session::validators(), vec![2, 3]);
+	});
+}
+
+#[test]
+fn only_add_remove_origin_manages_the_set() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(ValidatorSet::add_validator(Origin::signed(1), 3), DispatchError::BadOrigin);
+		assert_noop!(
+			ValidatorSet::remove_validator(Origin::signed(1), 2),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn set_cannot_shrink_below_minimum() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			ValidatorSet::add_validator(Origin::root(), 1),
+			Error::<Test>::AlreadyValidator
+		);
+		assert_noop!(
+			ValidatorSet::remove_validator(Origin::root(), 9),
+			Error::<Test>::NotValidator
+		);
+
+		assert_ok!(ValidatorSet::remove_validator(Origin::root(), 1));
+		assert_noop!(
+			ValidatorSet::remove_validator(Origin::root(), 2),
+			Error::<Test>::TooFewValidators
+		);
+	});
+}
+
+#[test]
+fn validators_must_register_session_keys() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(ValidatorSet::add_validator(Origin::root(), 3), Error::<Test>::NoSessionKeys);
+
+		set_keys(3);
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+	});
+}
+
+#[test]
+fn set_cannot_grow_beyond_maximum() {
+	new_test_ext().execute_with(|| {
+		set_keys(3);
+		set_keys(4);
+		assert_ok!(ValidatorSet::add_validator(Origin::root(), 3));
+		assert_noop!(
+			ValidatorSet::add_validator(Origin::root(), 4),
+			Error::<Test>::TooManyValidators
+		);
+	});
+}
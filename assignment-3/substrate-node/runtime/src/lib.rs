@@ -6,6 +6,11 @@
 #[cfg(feature = "std")]
 include!(concat!(env!("OUT_DIR"), "/wasm_binary.rs"));
 
+use frame_support::{
+	weights::{WeightToFeeCoefficient, WeightToFeeCoefficients, WeightToFeePolynomial},
+	PalletId,
+};
+use frame_system::EnsureRoot;
 use pallet_grandpa::{
 	fg_primitives, AuthorityId as GrandpaId, AuthorityList as GrandpaAuthorityList,
 };
@@ -14,7 +19,10 @@ use sp_consensus_aura::sr25519::AuthorityId as AuraId;
 use sp_core::{crypto::KeyTypeId, OpaqueMetadata};
 use sp_runtime::{
 	create_runtime_str, generic, impl_opaque_keys,
-	traits::{AccountIdLookup, BlakeTwo256, Block as BlockT, IdentifyAccount, NumberFor, Verify},
+	traits::{
+		AccountIdLookup, BlakeTwo256, Block as BlockT, ConvertInto, IdentifyAccount, NumberFor,
+		OpaqueKeys, Verify,
+	},
 	transaction_validity::{TransactionSource, TransactionValidity},
 	ApplyExtrinsicResult, MultiSignature,
 };
@@ -46,6 +54,21 @@ pub use pallet_template;
 /// Import the template pallet.
 pub use pallet_zodiac;
 
+/// Import the storage deal pallet.
+pub use pallet_storage_deal;
+
+/// Import the author reward pallet.
+pub use pallet_author_reward;
+
+/// Import the validator set pallet.
+pub use pallet_validator_set;
+
+/// Import the checkpoint pallet.
+pub use pallet_checkpoint;
+
+/// Import the organizations pallet.
+pub use pallet_organizations;
+
 /// An index to a block.
 pub type BlockNumber = u32;
 
@@ -124,6 +147,11 @@ pub const MINUTES: BlockNumber = 60_000 / (MILLISECS_PER_BLOCK as BlockNumber);
 pub const HOURS: BlockNumber = MINUTES * 60;
 pub const DAYS: BlockNumber = HOURS * 24;
 
+// Currency units, with 12 decimals.
+pub const UNIT: Balance = 1_000_000_000_000;
+pub const MILLIUNIT: Balance = UNIT / 1_000;
+pub const MICROUNIT: Balance = UNIT / 1_000_000;
+
 /// The version information used to identify this runtime when compiled natively.
 #[cfg(feature = "std")]
 pub fn native_version() -> NativeVersion {
@@ -202,6 +230,39 @@ parameter_types! {
 	pub const MaxAuthorities: u32 = 32;
 }
 
+parameter_types! {
+	pub const MinAuthorities: u32 = 1;
+}
+
+/// Configure the pallet-validator-set in pallets/validator-set.
+impl pallet_validator_set::Config for Runtime {
+	type Event = Event;
+	type AddRemoveOrigin = EnsureRoot<AccountId>;
+	type MinAuthorities = MinAuthorities;
+	type MaxAuthorities = MaxAuthorities;
+}
+
+parameter_types! {
+	pub const SessionPeriod: BlockNumber = 10 * MINUTES;
+	pub const SessionOffset: BlockNumber = 0;
+	pub const DisabledValidatorsThreshold: Perbill = Perbill::from_percent(33);
+}
+
+impl pallet_session::Config for Runtime {
+	type Event = Event;
+	type ValidatorId = AccountId;
+	type ValidatorIdOf = ConvertInto;
+	/// Validator set changes from pallet-validator-set take effect on session boundaries.
+	type ShouldEndSession = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type NextSessionRotation = pallet_session::PeriodicSessions<SessionPeriod, SessionOffset>;
+	type SessionManager = ValidatorSet;
+	/// Aura and GRANDPA pick up the keys registered through `Session::set_keys`.
+	type SessionHandler = <opaque::SessionKeys as OpaqueKeys>::KeyTypeIdProviders;
+	type Keys = opaque::SessionKeys;
+	type DisabledValidatorsThreshold = DisabledValidatorsThreshold;
+	type WeightInfo = ();
+}
+
 impl pallet_aura::Config for Runtime {
 	type AuthorityId = AuraId;
 	type DisabledValidators = ();
@@ -259,14 +320,45 @@ impl pallet_balances::Config for Runtime {
 	type WeightInfo = pallet_balances::weights::SubstrateWeight<Runtime>;
 }
 
+/// Fee charged for the weight of an extrinsic.
+///
+/// The linear term is tuned so that an extrinsic of `ExtrinsicBaseWeight` costs about one
+/// `MILLIUNIT`. A small quadratic term on top makes heavy calls increasingly expensive: it is
+/// negligible for ordinary extrinsics but dominates as a call approaches the block limit.
+pub struct WeightToFee;
+
+impl WeightToFeePolynomial for WeightToFee {
+	type Balance = Balance;
+
+	fn polynomial() -> WeightToFeeCoefficients<Self::Balance> {
+		let p = MILLIUNIT;
+		let q = Balance::from(ExtrinsicBaseWeight::get());
+		smallvec::smallvec![
+			WeightToFeeCoefficient {
+				degree: 1,
+				negative: false,
+				coeff_frac: Perbill::from_rational(p % q, q),
+				coeff_integer: p / q,
+			},
+			WeightToFeeCoefficient {
+				degree: 2,
+				negative: false,
+				coeff_frac: Perbill::from_parts(1),
+				coeff_integer: 0,
+			},
+		]
+	}
+}
+
 parameter_types! {
-	pub const TransactionByteFee: Balance = 1;
+	/// Charged per byte of the encoded extrinsic, on top of the weight fee.
+	pub const TransactionByteFee: Balance = 10 * MICROUNIT;
 }
 
 impl pallet_transaction_payment::Config for Runtime {
-	type OnChargeTransaction = CurrencyAdapter<Balances, ()>;
+	type OnChargeTransaction = CurrencyAdapter<Balances, AuthorReward>;
 	type TransactionByteFee = TransactionByteFee;
-	type WeightToFee = IdentityFee<Balance>;
+	type WeightToFee = WeightToFee;
 	type FeeMultiplierUpdate = ();
 }
 
@@ -286,6 +378,106 @@ impl pallet_zodiac::Config for Runtime {
 	type MyCurrency = Balances;
 }
 
+parameter_types! {
+	pub const ProviderBond: Balance = 100 * UNIT;
+	pub const MaxReplication: u32 = 8;
+	pub const MinDealDuration: BlockNumber = HOURS;
+	pub const MaxDealDuration: BlockNumber = 365 * DAYS;
+	pub const AttestationPeriod: BlockNumber = 6 * HOURS;
+	pub const MaxProposalAge: BlockNumber = 7 * DAYS;
+}
+
+/// Configure the pallet-storage-deal in pallets/storage-deal.
+impl pallet_storage_deal::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	type ProviderBond = ProviderBond;
+	type MaxReplication = MaxReplication;
+	type MinDealDuration = MinDealDuration;
+	type MaxDealDuration = MaxDealDuration;
+	type AttestationPeriod = AttestationPeriod;
+	type MaxProposalAge = MaxProposalAge;
+}
+
+parameter_types! {
+	pub const TreasuryPalletId: PalletId = PalletId(*b"py/trsry");
+	pub const ProposalBond: Permill = Permill::from_percent(5);
+	pub const ProposalBondMinimum: Balance = UNIT;
+	pub const SpendPeriod: BlockNumber = DAYS;
+	pub const Burn: Permill = Permill::zero();
+	pub const MaxApprovals: u32 = 100;
+}
+
+/// Keeps block rewards and fees nobody could be credited with. Root approves spending them.
+impl pallet_treasury::Config for Runtime {
+	type PalletId = TreasuryPalletId;
+	type Currency = Balances;
+	type ApproveOrigin = EnsureRoot<AccountId>;
+	type RejectOrigin = EnsureRoot<AccountId>;
+	type Event = Event;
+	type OnSlash = Treasury;
+	type ProposalBond = ProposalBond;
+	type ProposalBondMinimum = ProposalBondMinimum;
+	type SpendPeriod = SpendPeriod;
+	type Burn = Burn;
+	type BurnDestination = ();
+	type SpendFunds = ();
+	type WeightInfo = pallet_treasury::weights::SubstrateWeight<Runtime>;
+	type MaxApprovals = MaxApprovals;
+}
+
+parameter_types! {
+	pub const AuthorRewardPerBlock: Balance = 10 * MILLIUNIT;
+	pub TreasuryAccount: AccountId = Treasury::account_id();
+}
+
+/// Configure the pallet-author-reward in pallets/author-reward.
+impl pallet_author_reward::Config for Runtime {
+	type Event = Event;
+	type Currency = Balances;
+	/// Maps the index of the Aura author to the validator account of the current session, so
+	/// rewards follow the validator across key rotations.
+	type FindAuthor = pallet_session::FindAccountFromAuthorIndex<Self, Aura>;
+	type FallbackAccount = TreasuryAccount;
+	type DefaultReward = AuthorRewardPerBlock;
+	type UpdateOrigin = EnsureRoot<AccountId>;
+}
+
+/// Configure the pallet-checkpoint in pallets/checkpoint.
+impl pallet_checkpoint::Config for Runtime {
+	type Event = Event;
+}
+
+parameter_types! {
+	pub const OrganizationsPalletId: PalletId = PalletId(*b"py/orgss");
+	pub const MaxOrgMembers: u32 = 64;
+}
+
+/// Admins may make any call for their organization, registrars manage its storage deals and
+/// auditors may only settle them.
+pub struct OrgRoleFilter;
+
+impl pallet_organizations::RoleFilter<Call> for OrgRoleFilter {
+	fn allows(role: pallet_organizations::Role, call: &Call) -> bool {
+		use pallet_organizations::Role;
+		match role {
+			Role::Admin => true,
+			Role::Registrar => matches!(call, Call::StorageDeal(_)),
+			Role::Auditor =>
+				matches!(call, Call::StorageDeal(pallet_storage_deal::Call::settle { .. })),
+		}
+	}
+}
+
+/// Configure the pallet-organizations in pallets/organizations.
+impl pallet_organizations::Config for Runtime {
+	type Event = Event;
+	type Call = Call;
+	type PalletId = OrganizationsPalletId;
+	type MaxMembers = MaxOrgMembers;
+	type RoleFilter = OrgRoleFilter;
+}
+
 // Create the runtime by composing the FRAME pallets that were previously configured.
 construct_runtime!(
 	pub enum Runtime where
@@ -296,14 +488,23 @@ construct_runtime!(
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
 		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
+		// The validator set and session pallets must come before Aura and GRANDPA so that
+		// session changes are applied before consensus reads its authorities.
+		ValidatorSet: pallet_validator_set::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Session: pallet_session::{Pallet, Call, Storage, Config<T>, Event},
 		Aura: pallet_aura::{Pallet, Config<T>},
 		Grandpa: pallet_grandpa::{Pallet, Call, Storage, Config, Event},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
 		TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
 		Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
+		Treasury: pallet_treasury::{Pallet, Call, Storage, Config, Event<T>},
 		// Include the custom logic from the pallet-template in the runtime.
 		TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
 		Zodiac: pallet_zodiac::{Pallet, Call, Storage, Event<T>},
+		StorageDeal: pallet_storage_deal::{Pallet, Call, Storage, Event<T>},
+		AuthorReward: pallet_author_reward::{Pallet, Call, Storage, Event<T>},
+		Checkpoint: pallet_checkpoint::{Pallet, Call, Storage, Inherent, Event<T>},
+		Organizations: pallet_organizations::{Pallet, Call, Storage, Event<T>},
 	}
 );
 
@@ -521,3 +722,101 @@ impl_runtime_apis! {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::Encode;
+	use frame_support::weights::GetDispatchInfo;
+	use sp_core::sr25519;
+	use sp_runtime::generic::Era;
+
+	/// A balance transfer as a wallet would sign it. Only the length of the signature matters.
+	fn signed_transfer() -> UncheckedExtrinsic {
+		let call = Call::Balances(BalancesCall::transfer {
+			dest: sp_runtime::MultiAddress::Id(AccountId::new([2; 32])),
+			value: UNIT,
+		});
+		signed(call)
+	}
+
+	fn signed(call: Call) -> UncheckedExtrinsic {
+		let extra: SignedExtra = (
+			frame_system::CheckSpecVersion::new(),
+			frame_system::CheckTxVersion::new(),
+			frame_system::CheckGenesis::new(),
+			frame_system::CheckEra::from(Era::mortal(256, 0)),
+			frame_system::CheckNonce::from(0),
+			frame_system::CheckWeight::new(),
+			pallet_transaction_payment::ChargeTransactionPayment::from(0),
+		);
+		UncheckedExtrinsic::new_signed(
+			call,
+			sp_runtime::MultiAddress::Id(AccountId::new([1; 32])),
+			sr25519::Signature::from_raw([0; 64]).into(),
+			extra,
+		)
+	}
+
+	/// Fee charged by transaction payment for `call` submitted as an extrinsic of `len` bytes.
+	fn fee_of(call: &Call, len: u32) -> Balance {
+		let info = call.get_dispatch_info();
+		sp_io::TestExternalities::default()
+			.execute_with(|| TransactionPayment::compute_fee(len, &info, 0))
+	}
+
+	/// Fee of the signed extrinsic `xt`, with its actual encoded length.
+	fn fee_of_signed(xt: &UncheckedExtrinsic) -> Balance {
+		fee_of(&xt.function, xt.encode().len() as u32)
+	}
+
+	#[test]
+	fn base_extrinsic_costs_about_one_milliunit() {
+		let fee = WeightToFee::calc(&ExtrinsicBaseWeight::get());
+		// The quadratic term adds less than 2% at this weight.
+		assert!((MILLIUNIT..MILLIUNIT + MILLIUNIT / 50).contains(&fee), "base fee {}", fee);
+	}
+
+	#[test]
+	fn balance_transfer_fee_is_predictable() {
+		let fee = fee_of_signed(&signed_transfer());
+		assert!(fee > 3 * MILLIUNIT, "transfer fee {} too low", fee);
+		assert!(fee < 10 * MILLIUNIT, "transfer fee {} too high", fee);
+	}
+
+	#[test]
+	fn storage_deal_proposal_fee_is_predictable() {
+		let call = Call::StorageDeal(pallet_storage_deal::Call::propose_deal {
+			content: Hash::repeat_byte(1),
+			size: 1024,
+			replication: 3,
+			price_per_block: MILLIUNIT,
+			duration: DAYS,
+		});
+		let fee = fee_of_signed(&signed(call));
+		assert!(fee > 3 * MILLIUNIT, "proposal fee {} too low", fee);
+		assert!(fee < 10 * MILLIUNIT, "proposal fee {} too high", fee);
+	}
+
+	#[test]
+	fn length_fee_is_linear() {
+		let xt = signed_transfer();
+		let len = xt.encode().len() as u32;
+		assert_eq!(fee_of(&xt.function, len + 1_000) - fee_of(&xt.function, len), 10 * MILLIUNIT);
+	}
+
+	#[test]
+	fn heavy_calls_cost_superlinearly() {
+		let light = ExtrinsicBaseWeight::get();
+		let heavy = 1_000 * light;
+		assert!(WeightToFee::calc(&heavy) > 1_000 * WeightToFee::calc(&light));
+
+		let max = BlockWeights::get().max_block;
+		assert!(WeightToFee::calc(&max) > 1_000 * UNIT);
+	}
+
+	#[test]
+	fn provider_bond_outweighs_fees() {
+		assert!(ProviderBond::get() > 1_000 * fee_of_signed(&signed_transfer()));
+	}
+}